@@ -1,16 +1,36 @@
 pub mod ast;
-mod reference_db;
+pub(crate) mod reference_db;
+
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+};
 
 use ast::RootSchema;
 use reference_db::ReferenceDatabase;
 
+use crate::{
+    frontend::{Dialect, Document, Message},
+    Config, DocumentLoader,
+};
 use openapiv3::{
-    OpenAPI, Operation, Parameter, ParameterData, ParameterSchemaOrContent, PathItem, Paths,
-    ReferenceOr, Schema, SchemaKind, Type,
+    Content, Header, IntegerFormat, NumberType, OpenAPI, Operation, Parameter, ParameterData,
+    ParameterSchemaOrContent, PathItem, PathStyle, Paths, QueryStyle, ReferenceOr, RequestBody,
+    Schema, SchemaData, SchemaKind, Type, VariantOrUnknownOrEmpty,
 };
-
-pub fn build(openapi: &OpenAPI) -> AstResult {
-    let builder = AstBuilder::new(openapi);
+use url::Url;
+
+/// Header parameters which are ignored by the OpenAPI Specification.
+const IGNORED_HEADERS: [&str; 3] = ["Accept", "Content-Type", "Authorization"];
+
+/// `base` is the URI of the document, which relative references are resolved against.
+pub fn build<'a>(
+    document: &'a Document,
+    base: &Url,
+    config: &'a Config,
+    loader: &'a dyn DocumentLoader,
+) -> AstResult {
+    let builder = AstBuilder::new(document, base, config, loader);
     builder.build()
 }
 
@@ -21,16 +41,46 @@ pub struct AstResult {
 
 struct AstBuilder<'a> {
     openapi: &'a OpenAPI,
+    dialect: Dialect,
+    messages: &'a [Message],
+    config: &'a Config,
     errors: Vec<String>,
     db: ReferenceDatabase<'a>,
+    /// References of schemas being expanded, innermost last.
+    resolving_references: Vec<String>,
+    /// Names of schemas that are emitted as named schemas, keyed by reference.
+    named_schema_names: HashMap<String, String>,
+    /// References of the roots of a JSON Schema document which are named only to be validated on their own,
+    /// since other schemas inline them with `--inline-schemas`.
+    inlined_roots: HashSet<String>,
+    named_schemas: Vec<ast::NamedSchema>,
 }
 
 impl<'a> AstBuilder<'a> {
-    fn new(openapi: &'a OpenAPI) -> Self {
+    fn new(
+        document: &'a Document,
+        base: &Url,
+        config: &'a Config,
+        loader: &'a dyn DocumentLoader,
+    ) -> Self {
         Self {
-            openapi,
+            openapi: &document.openapi,
+            dialect: document.dialect,
+            messages: &document.messages,
+            config,
             errors: Vec::new(),
-            db: ReferenceDatabase::new(openapi),
+            db: ReferenceDatabase::new(
+                document.raw.clone(),
+                base,
+                loader,
+                &config.resolution,
+                document.dialect,
+            )
+            .with_number_literals(document.number_literals.clone()),
+            resolving_references: Vec::new(),
+            named_schema_names: HashMap::new(),
+            inlined_roots: HashSet::new(),
+            named_schemas: Vec::new(),
         }
     }
 
@@ -57,14 +107,63 @@ impl<'a> AstBuilder<'a> {
     }
 
     fn build(mut self) -> AstResult {
+        if self.dialect == Dialect::JsonSchema {
+            self.build_standalone_schemas();
+        } else {
+            self.build_component_schemas();
+        }
         let path_items = self.build_paths(&self.openapi.paths);
+        let messages = self
+            .messages
+            .iter()
+            .filter_map(|message| self.build_message(message))
+            .collect();
+        let mut diagnostics = self.db.take_diagnostics();
+        self.errors.append(&mut diagnostics);
 
         AstResult {
-            ast: RootSchema { path_items },
+            ast: RootSchema {
+                path_items,
+                messages,
+                named_schemas: self.named_schemas,
+            },
             errors: self.errors,
         }
     }
 
+    /// Builds every object in `components/schemas` as a named schema,
+    /// even if it is not referenced by any operation.
+    fn build_component_schemas(&mut self) {
+        if self.config.inline_schemas {
+            return;
+        }
+        let Some(components) = &self.openapi.components else {
+            return;
+        };
+
+        for name in components.schemas.keys() {
+            self.build_schema_reference(&format!("#/components/schemas/{name}"), name);
+        }
+    }
+
+    /// Builds every schema in `components/schemas` as a named schema,
+    /// since each of them is validated on its own.
+    fn build_standalone_schemas(&mut self) {
+        let Some(components) = &self.openapi.components else {
+            return;
+        };
+
+        for name in components.schemas.keys() {
+            let reference = format!("#/components/schemas/{name}");
+            match self.build_schema_reference(&reference, name) {
+                Some((ast::Type::Ref { .. }, _)) | None => (),
+                Some(_) => {
+                    self.add_error(format!("Only `object` is supported at the root of {name}"))
+                }
+            }
+        }
+    }
+
     fn build_paths(&mut self, paths: &Paths) -> Vec<ast::PathItem> {
         let mut path_items = vec![];
 
@@ -72,13 +171,14 @@ impl<'a> AstBuilder<'a> {
             let item = match item {
                 ReferenceOr::Item(item) => item.clone(),
                 ReferenceOr::Reference { reference } => {
-                    if let Ok(item) = self.db.resolve_path_item(reference) {
-                        item.clone()
-                    } else {
-                        self.add_error(format!(
-                            "Failed to resolve reference. reference: {reference}"
-                        ));
-                        continue;
+                    match self.db.resolve::<PathItem>(reference) {
+                        Ok(item) => item,
+                        Err(err) => {
+                            self.add_error(format!(
+                                "Failed to resolve reference of path item. path: {path_name}, reference: {reference}, error: {err}"
+                            ));
+                            continue;
+                        }
                     }
                 }
             };
@@ -88,7 +188,7 @@ impl<'a> AstBuilder<'a> {
                 url: path_name.clone(),
                 operations: operations
                     .iter()
-                    .map(|ope| self.build_operation(ope))
+                    .map(|ope| self.build_operation(path_name, ope))
                     .collect::<Vec<_>>(),
             });
         }
@@ -96,7 +196,7 @@ impl<'a> AstBuilder<'a> {
         path_items
     }
 
-    fn build_operation(&mut self, operation: &Operation) -> ast::Operation {
+    fn build_operation(&mut self, url: &str, operation: &Operation) -> ast::Operation {
         let ope_id = if let Some(id) = &operation.operation_id {
             Some(id.clone())
         } else {
@@ -105,77 +205,410 @@ impl<'a> AstBuilder<'a> {
         };
 
         let mut queries = vec![];
+        let mut path_params = vec![];
+        let mut headers = vec![];
         for param in &operation.parameters {
             let param = match param {
                 ReferenceOr::Item(param) => param.clone(),
                 ReferenceOr::Reference { reference } => {
-                    self.db.resolve_parameter(reference).unwrap().clone()
+                    match self.db.resolve::<Parameter>(reference) {
+                        Ok(param) => param,
+                        Err(err) => {
+                            self.add_error(format!(
+                                "Failed to resolve reference. reference: {reference}, error: {err}"
+                            ));
+                            continue;
+                        }
+                    }
                 }
             };
 
             match param {
-                Parameter::Query { parameter_data, .. } => {
-                    if let Some(query) = self.build_param(&parameter_data) {
+                // `allowReserved` only changes percent-encoding, which Rack decodes either way.
+                Parameter::Query {
+                    parameter_data,
+                    style,
+                    allow_empty_value,
+                    ..
+                } => {
+                    if let Some(mut query) = self.build_param(&parameter_data) {
+                        query.allow_empty_value = allow_empty_value.unwrap_or(false);
+                        // `style` and `explode` apply to `schema`, not to `content`.
+                        if query.serialization == ast::Serialization::Native {
+                            query.serialization =
+                                self.query_serialization(&query, &style, parameter_data.explode);
+                        }
                         queries.push(query);
                     }
                 }
-                _ => unimplemented!(),
+                Parameter::Path {
+                    parameter_data,
+                    style,
+                } => {
+                    if !url.contains(&format!("{{{}}}", parameter_data.name)) {
+                        self.add_error(format!(
+                            "Path parameter is not found in the path. path: {url}, name: {}",
+                            parameter_data.name
+                        ));
+                        continue;
+                    }
+                    if let Some(mut path_param) = self.build_param(&parameter_data) {
+                        // Path parameters are always required.
+                        path_param.required = true;
+                        if path_param.serialization == ast::Serialization::Native {
+                            path_param.serialization = path_serialization(
+                                &path_param,
+                                &style,
+                                parameter_data.explode.unwrap_or(false),
+                            );
+                        }
+                        path_params.push(path_param);
+                    }
+                }
+                Parameter::Header { parameter_data, .. } => {
+                    // These are described by other fields of the operation.
+                    if IGNORED_HEADERS
+                        .iter()
+                        .any(|ignored| ignored.eq_ignore_ascii_case(&parameter_data.name))
+                    {
+                        continue;
+                    }
+                    if let Some(mut header) = self.build_param(&parameter_data) {
+                        // `simple` is the only style of headers.
+                        if header.serialization == ast::Serialization::Native {
+                            header.serialization = path_serialization(
+                                &header,
+                                &PathStyle::Simple,
+                                parameter_data.explode.unwrap_or(false),
+                            );
+                        }
+                        headers.push(header);
+                    }
+                }
+                Parameter::Cookie { parameter_data, .. } => {
+                    self.add_unsupported_error("in: cookie", &parameter_data.name);
+                }
+            }
+        }
+
+        let request_body = match &operation.request_body {
+            Some(ReferenceOr::Item(request_body)) => {
+                self.build_body(&request_body.content, "requestBody")
+            }
+            Some(ReferenceOr::Reference { reference }) => {
+                match self.db.resolve::<RequestBody>(reference) {
+                    Ok(request_body) => self.build_body(&request_body.content, "requestBody"),
+                    Err(err) => {
+                        self.add_error(format!(
+                            "Failed to resolve reference. reference: {reference}, error: {err}"
+                        ));
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let mut responses = vec![];
+        let default_response = operation
+            .responses
+            .default
+            .as_ref()
+            .map(|response| ("default".to_string(), response));
+        let status_responses = operation
+            .responses
+            .responses
+            .iter()
+            .map(|(status, response)| (status.to_string(), response));
+        for (status, response) in status_responses.chain(default_response) {
+            let response = match response {
+                ReferenceOr::Item(response) => response.clone(),
+                ReferenceOr::Reference { reference } => {
+                    match self.db.resolve::<openapiv3::Response>(reference) {
+                        Ok(response) => response,
+                        Err(err) => {
+                            self.add_error(format!(
+                                "Failed to resolve reference. reference: {reference}, error: {err}"
+                            ));
+                            continue;
+                        }
+                    }
+                }
+            };
+            let headers = self.build_response_headers(&response);
+            let body = self.build_body(&response.content, &format!("response {status}"));
+            if body.is_some() || !headers.is_empty() {
+                responses.push(ast::Response {
+                    status,
+                    headers,
+                    body,
+                });
             }
         }
 
         ast::Operation {
             id: ope_id,
             queries,
+            path_params,
+            headers,
+            request_body,
+            responses,
         }
     }
 
-    fn build_param(&mut self, param: &ParameterData) -> Option<ast::Schema> {
-        let ty = match &param.format {
-            ParameterSchemaOrContent::Schema(schema) => {
-                let schema = match schema {
-                    ReferenceOr::Item(schema) => schema.clone(),
-                    ReferenceOr::Reference { reference } => {
-                        self.db.resolve_schema(reference).unwrap().clone()
+    fn build_response_headers(&mut self, response: &openapiv3::Response) -> Vec<ast::Schema> {
+        let mut schemas = vec![];
+        for (name, header) in &response.headers {
+            // `Content-Type` of a response is described by its `content`.
+            if name.eq_ignore_ascii_case("Content-Type") {
+                continue;
+            }
+            let header = match header {
+                ReferenceOr::Item(header) => header.clone(),
+                ReferenceOr::Reference { reference } => {
+                    match self.db.resolve::<Header>(reference) {
+                        Ok(header) => header,
+                        Err(err) => {
+                            self.add_error(format!(
+                                "Failed to resolve reference. reference: {reference}, error: {err}"
+                            ));
+                            continue;
+                        }
                     }
-                };
-                self.build_schema(&schema, param)
+                }
+            };
+            let parameter_data = ParameterData {
+                name: name.clone(),
+                description: header.description,
+                required: header.required,
+                deprecated: header.deprecated,
+                format: header.format,
+                example: header.example,
+                examples: header.examples,
+                explode: None,
+                extensions: header.extensions,
+            };
+            if let Some(mut schema) = self.build_param(&parameter_data) {
+                if schema.serialization == ast::Serialization::Native {
+                    schema.serialization = path_serialization(&schema, &PathStyle::Simple, false);
+                }
+                schemas.push(schema);
             }
-            ParameterSchemaOrContent::Content(_) => {
-                self.add_unsupported_error_by_param("Content", param);
+        }
+        schemas
+    }
+
+    /// Builds the JSON body, or the form body if there is no JSON one.
+    fn build_body(&mut self, content: &Content, ctx: &str) -> Option<ast::Body> {
+        let json = content
+            .iter()
+            .find(|(media_type, _)| is_json_media_type(media_type));
+        let form = content
+            .iter()
+            .find(|(media_type, _)| is_form_media_type(media_type));
+        let (form, media_type) = match (json, form) {
+            (Some((_, media_type)), _) => (false, media_type),
+            (None, Some((_, media_type))) => (true, media_type),
+            (None, None) => {
+                if !content.is_empty() {
+                    let media_types = content.keys().cloned().collect::<Vec<_>>().join(", ");
+                    self.add_error(format!(
+                        "Only JSON and forms are supported in {ctx}. media types: {media_types}"
+                    ));
+                }
                 return None;
             }
-        }?;
+        };
+        let (ty, _) = self.build_reference_or_schema(media_type.schema.as_ref()?, ctx)?;
+
+        let body = self.build_body_type(ty, ctx)?;
+        Some(ast::Body { form, ..body })
+    }
+
+    fn build_message(&mut self, message: &Message) -> Option<ast::Message> {
+        let ctx = format!("message {}", message.name);
+        let (ty, _) = self.build_schema_reference(&message.payload, &ctx)?;
+
+        Some(ast::Message {
+            name: message.name.clone(),
+            body: self.build_body_type(ty, &ctx)?,
+        })
+    }
+
+    fn build_body_type(&mut self, ty: ast::Type, ctx: &str) -> Option<ast::Body> {
+        match ty {
+            ast::Type::Object { properties, .. } => Some(ast::Body {
+                properties,
+                form: false,
+            }),
+            // The root of a body is expanded one level even if the schema is recursive.
+            ast::Type::Ref { name, .. } => match self.find_named_schema(&name) {
+                Some(ast::Type::Object { properties, .. }) => Some(ast::Body {
+                    properties: properties.clone(),
+                    form: false,
+                }),
+                _ => None,
+            },
+            _ => {
+                self.add_error(format!("Only `object` is supported at the root of {ctx}"));
+                None
+            }
+        }
+    }
+
+    fn build_param(&mut self, param: &ParameterData) -> Option<ast::Schema> {
+        let (ty, schema_data, serialization) = match &param.format {
+            ParameterSchemaOrContent::Schema(schema) => {
+                let (ty, schema_data) = self.build_reference_or_schema(schema, &param.name)?;
+                (ty, schema_data, ast::Serialization::Native)
+            }
+            ParameterSchemaOrContent::Content(content) => {
+                let Some((_, media_type)) = content
+                    .iter()
+                    .find(|(media_type, _)| is_json_media_type(media_type))
+                else {
+                    let media_types = content.keys().cloned().collect::<Vec<_>>().join(", ");
+                    self.add_error(format!(
+                        "Only JSON is supported in `content` of {}. media types: {media_types}",
+                        param.name
+                    ));
+                    return None;
+                };
+                let (ty, schema_data) =
+                    self.build_reference_or_schema(media_type.schema.as_ref()?, &param.name)?;
+                (ty, schema_data, ast::Serialization::Json)
+            }
+        };
 
         Some(ast::Schema {
             required: param.required,
+            nullable: schema_data.nullable,
             ty,
             name: param.name.clone(),
+            default: schema_data.default,
+            serialization,
+            // `allowEmptyValue` is only for queries.
+            allow_empty_value: true,
+            deprecated: param.deprecated.unwrap_or(false),
         })
     }
 
-    fn build_type(&mut self, ty: &Type, ctx: &ParameterData) -> ast::Type {
+    fn query_serialization(
+        &mut self,
+        query: &ast::Schema,
+        style: &QueryStyle,
+        explode: Option<bool>,
+    ) -> ast::Serialization {
+        let delimiter = match style {
+            QueryStyle::Form => ",",
+            QueryStyle::SpaceDelimited => " ",
+            QueryStyle::PipeDelimited => "|",
+            QueryStyle::DeepObject => {
+                if !is_object_type(&query.ty) {
+                    self.add_error(format!(
+                        "`deepObject` is only defined for objects in {}",
+                        query.name
+                    ));
+                }
+                return ast::Serialization::Native;
+            }
+        };
+        if explode.unwrap_or(*style == QueryStyle::Form) {
+            return match &query.ty {
+                ast::Type::Array { .. } => {
+                    self.add_error(format!(
+                        "Rack keeps only the last of repeated keys, so an exploded array is read as `{0}[]=1&{0}[]=2` in {0}",
+                        query.name
+                    ));
+                    ast::Serialization::Native
+                }
+                ty if *style == QueryStyle::Form && is_object_type(ty) => {
+                    ast::Serialization::Exploded {
+                        keys: self.property_keys(ty),
+                    }
+                }
+                _ => ast::Serialization::Native,
+            };
+        }
+
+        match &query.ty {
+            ast::Type::Array { .. } => ast::Serialization::Delimited {
+                prefix: String::new(),
+                delimiter: delimiter.to_string(),
+            },
+            ty if is_object_type(ty) => ast::Serialization::DelimitedPairs {
+                prefix: String::new(),
+                delimiter: delimiter.to_string(),
+            },
+            _ => ast::Serialization::Native,
+        }
+    }
+
+    fn build_type(&mut self, ty: &Type, schema_data: &SchemaData, ctx: &str) -> ast::Type {
         match ty {
             Type::Integer(integer) => {
                 let mut validates = vec![];
-                if let Some(max) = integer.maximum {
-                    let max = if integer.exclusive_maximum {
+                let format_bounds =
+                    integer_format_bounds(&integer.format, self.config.int64_bounds);
+
+                let max = integer.maximum.map(|max| {
+                    if integer.exclusive_maximum {
                         max - 1
                     } else {
                         max
-                    };
+                    }
+                });
+                // Narrow to the range of the format so that Ruby does not accept it as a Bignum.
+                let max = match (max, format_bounds) {
+                    (Some(max), Some((_, format_max))) => Some(max.min(format_max)),
+                    (None, Some((_, format_max))) => Some(format_max),
+                    (max, None) => max,
+                };
+                if let Some(max) = max {
                     validates.push(ast::Validate::Max(max));
                 }
-                if let Some(min) = integer.minimum {
-                    let min = if integer.exclusive_minimum {
+
+                let min = integer.minimum.map(|min| {
+                    if integer.exclusive_minimum {
                         min + 1
                     } else {
                         min
-                    };
+                    }
+                });
+                let min = match (min, format_bounds) {
+                    (Some(min), Some((format_min, _))) => Some(min.max(format_min)),
+                    (None, Some((format_min, _))) => Some(format_min),
+                    (min, None) => min,
+                };
+                if let Some(min) = min {
                     validates.push(ast::Validate::Min(min));
                 }
+                validates.extend(enum_validate(&integer.enumeration));
 
                 ast::Type::Integer { validates }
             }
+            Type::Number(number) if is_decimal(number, schema_data) => {
+                let mut validates = vec![];
+                if let Some(max) = number.maximum {
+                    let max = self.decimal_bound(max);
+                    if number.exclusive_maximum {
+                        validates.push(ast::Validate::ExclusiveMaxDecimal(max));
+                    } else {
+                        validates.push(ast::Validate::MaxDecimal(max));
+                    }
+                }
+                if let Some(min) = number.minimum {
+                    let min = self.decimal_bound(min);
+                    if number.exclusive_minimum {
+                        validates.push(ast::Validate::ExclusiveMinDecimal(min));
+                    } else {
+                        validates.push(ast::Validate::MinDecimal(min));
+                    }
+                }
+                validates.extend(enum_validate(&number.enumeration));
+
+                ast::Type::Decimal { validates }
+            }
             Type::Number(number) => {
                 let mut validates = vec![];
                 if let Some(max) = number.maximum {
@@ -194,6 +627,7 @@ impl<'a> AstBuilder<'a> {
                     };
                     validates.push(ast::Validate::MinF(min));
                 }
+                validates.extend(enum_validate(&number.enumeration));
 
                 ast::Type::Number { validates }
             }
@@ -205,6 +639,7 @@ impl<'a> AstBuilder<'a> {
                 if let Some(min) = string.min_length {
                     validates.push(ast::Validate::MinLength(min));
                 }
+                validates.extend(enum_validate(&string.enumeration));
 
                 ast::Type::String { validates }
             }
@@ -217,15 +652,21 @@ impl<'a> AstBuilder<'a> {
                 if let Some(min) = array.min_items {
                     validates.push(ast::Validate::MinItems(min));
                 }
+                if array.unique_items {
+                    validates.push(ast::Validate::UniqueItems);
+                }
 
                 let item_ty = if let Some(item_schema) = &array.items {
-                    let schema = match item_schema {
-                        ReferenceOr::Item(schema) => *schema.clone(),
-                        ReferenceOr::Reference { reference } => {
-                            self.db.resolve_schema(reference).unwrap().clone()
-                        }
-                    };
-                    self.build_schema(&schema, ctx)
+                    self.build_reference_or_schema(item_schema, ctx).map(
+                        |(ty, item_schema_data)| {
+                            if item_schema_data.nullable {
+                                self.add_error(format!(
+                                    "`nullable` is not supported in items of {ctx}"
+                                ));
+                            }
+                            ty
+                        },
+                    )
                 } else {
                     None
                 };
@@ -238,22 +679,21 @@ impl<'a> AstBuilder<'a> {
             Type::Object(object) => {
                 let mut properties = vec![];
                 for property in object.properties.iter() {
-                    let schema = match property.1 {
-                        ReferenceOr::Item(item) => *item.clone(),
-                        ReferenceOr::Reference { reference } => {
-                            self.db.resolve_schema(reference).unwrap().clone()
-                        }
-                    };
-                    let ty = if let Some(ty) = self.build_schema(&schema, ctx) {
-                        ty
-                    } else {
-                        continue;
-                    };
+                    let (ty, schema_data) =
+                        if let Some(ty) = self.build_reference_or_schema(property.1, ctx) {
+                            ty
+                        } else {
+                            continue;
+                        };
 
                     properties.push(ast::Property {
                         required: object.required.contains(property.0),
+                        nullable: schema_data.nullable,
                         key: property.0.clone(),
                         value: ty,
+                        default: schema_data.default,
+                        read_only: schema_data.read_only,
+                        write_only: schema_data.write_only,
                     });
                 }
 
@@ -265,37 +705,289 @@ impl<'a> AstBuilder<'a> {
         }
     }
 
-    fn build_schema(&mut self, schema: &Schema, ctx: &ParameterData) -> Option<ast::Type> {
+    fn build_reference_or_schema<T>(
+        &mut self,
+        schema: &ReferenceOr<T>,
+        ctx: &str,
+    ) -> Option<(ast::Type, SchemaData)>
+    where
+        T: Borrow<Schema>,
+    {
+        match schema {
+            ReferenceOr::Item(schema) => {
+                let schema = schema.borrow();
+                Some((self.build_schema(schema, ctx)?, schema.schema_data.clone()))
+            }
+            ReferenceOr::Reference { reference } => self.build_schema_reference(reference, ctx),
+        }
+    }
+
+    /// Builds an object in `components/schemas` as a named schema, and expands others in place.
+    /// If a schema refers to itself while it is expanded, it also becomes a named schema,
+    /// and the references inside of it are built as lazy `ast::Type::Ref`s.
+    fn build_schema_reference(
+        &mut self,
+        reference: &str,
+        ctx: &str,
+    ) -> Option<(ast::Type, SchemaData)> {
+        let schema = match self.db.resolve::<Schema>(reference) {
+            Ok(schema) => schema,
+            Err(err) => {
+                self.add_error(format!(
+                    "Failed to resolve reference. reference: {reference}, error: {err}"
+                ));
+                return None;
+            }
+        };
+        // References in external documents are absolute, so compare the normalized ones.
+        let reference = self.db.normalize_reference(reference).unwrap();
+        let reference = reference.as_str();
+
+        let is_resolving = self
+            .resolving_references
+            .iter()
+            .any(|resolving| resolving == reference);
+        let is_standalone_root =
+            self.dialect == Dialect::JsonSchema && self.resolving_references.is_empty();
+        if let Some(name) = self.named_schema_names.get(reference) {
+            if is_resolving || !self.inlined_roots.contains(reference) {
+                let ty = ast::Type::Ref {
+                    name: name.clone(),
+                    lazy: is_resolving,
+                };
+                return Some((ty, schema.schema_data));
+            }
+        }
+        if is_resolving {
+            let name = self.name_schema(reference);
+            let ty = ast::Type::Ref { name, lazy: true };
+            return Some((ty, schema.schema_data));
+        }
+        if (!self.config.inline_schemas || is_standalone_root)
+            && is_component_schema_reference(reference)
+            && matches!(schema.schema_kind, SchemaKind::Type(Type::Object(_)))
+        {
+            self.name_schema(reference);
+            if self.config.inline_schemas {
+                self.inlined_roots.insert(reference.to_string());
+            }
+        }
+
+        self.resolving_references.push(reference.to_string());
+        let ty = self.build_schema(&schema, ctx);
+        self.resolving_references.pop();
+        let ty = ty?;
+
+        match self.named_schema_names.get(reference) {
+            Some(name) if is_standalone_root || !self.inlined_roots.contains(reference) => {
+                let name = name.clone();
+                if !matches!(ty, ast::Type::Object { .. }) {
+                    self.add_error(format!(
+                        "Only `object` is supported in recursive schema. reference: {reference}"
+                    ));
+                }
+                // Pushed after its dependencies, so that they are defined first.
+                self.named_schemas.push(ast::NamedSchema {
+                    name: name.clone(),
+                    ty,
+                });
+                Some((ast::Type::Ref { name, lazy: false }, schema.schema_data))
+            }
+            _ => Some((ty, schema.schema_data)),
+        }
+    }
+
+    /// Assigns a unique name derived from the last segment of the reference.
+    fn name_schema(&mut self, reference: &str) -> String {
+        let base_name = reference
+            .rsplit(['/', '#'])
+            .find(|segment| !segment.is_empty())
+            .unwrap_or("schema");
+        let base_name = base_name
+            .split_once('.')
+            .map_or(base_name, |(file_stem, _)| file_stem);
+
+        let mut name = base_name.to_string();
+        let mut suffix = 1;
+        while self.named_schema_names.values().any(|named| *named == name) {
+            suffix += 1;
+            name = format!("{base_name}{suffix}");
+        }
+        self.named_schema_names
+            .insert(reference.to_string(), name.clone());
+
+        name
+    }
+
+    /// Keys of the properties of an object, or of the named schema it refers to.
+    fn property_keys(&self, ty: &ast::Type) -> Vec<String> {
+        let ty = match ty {
+            ast::Type::Ref { name, .. } => self.find_named_schema(name),
+            ty => Some(ty),
+        };
+        match ty {
+            Some(ast::Type::Object { properties, .. }) => properties
+                .iter()
+                .map(|property| property.key.clone())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn find_named_schema(&self, name: &str) -> Option<&ast::Type> {
+        self.named_schemas
+            .iter()
+            .find(|named_schema| named_schema.name == name)
+            .map(|named_schema| &named_schema.ty)
+    }
+
+    fn build_schema(&mut self, schema: &Schema, ctx: &str) -> Option<ast::Type> {
         match &schema.schema_kind {
-            SchemaKind::Type(ty) => Some(self.build_type(ty, ctx)),
+            SchemaKind::Type(ty) => Some(self.build_type(ty, &schema.schema_data, ctx)),
             SchemaKind::AllOf { .. } => {
-                self.add_unsupported_error_by_param("AllOf", ctx);
+                self.add_unsupported_error("AllOf", ctx);
                 None
             }
             SchemaKind::OneOf { .. } => {
-                self.add_unsupported_error_by_param("OneOf", ctx);
+                self.add_unsupported_error("OneOf", ctx);
                 None
             }
             SchemaKind::AnyOf { .. } => {
-                self.add_unsupported_error_by_param("AnyOf", ctx);
+                self.add_unsupported_error("AnyOf", ctx);
                 None
             }
             SchemaKind::Any(_) => {
-                self.add_unsupported_error_by_param("Any", ctx);
+                self.add_unsupported_error("Any", ctx);
                 None
             }
             SchemaKind::Not { .. } => {
-                self.add_unsupported_error_by_param("Not", ctx);
+                self.add_unsupported_error("Not", ctx);
                 None
             }
         }
     }
 
-    fn add_unsupported_error_by_param(&mut self, target: &str, param: &ParameterData) {
-        self.add_error(format!("`{target}` is not supported in {}", param.name));
+    fn add_unsupported_error(&mut self, target: &str, ctx: &str) {
+        self.add_error(format!("`{target}` is not supported in {ctx}"));
+    }
+
+    /// The bound as it is written, or its `f64` form if that is unknown.
+    fn decimal_bound(&mut self, bound: f64) -> String {
+        match self.db.number_literal(bound) {
+            Ok(literal) => literal,
+            Err(err) => {
+                self.add_error(err.to_string());
+                bound.to_string()
+            }
+        }
     }
 
     fn add_error(&mut self, message: String) {
         self.errors.push(message);
     }
 }
+
+fn is_component_schema_reference(reference: &str) -> bool {
+    reference
+        .strip_prefix("#/components/schemas/")
+        .is_some_and(|name| !name.contains('/'))
+}
+
+/// `application/json` and structured syntax suffixes such as `application/problem+json`.
+fn is_json_media_type(media_type: &str) -> bool {
+    let essence = media_type.split(';').next().unwrap_or_default().trim();
+    essence == "application/json" || essence.ends_with("+json")
+}
+
+fn is_form_media_type(media_type: &str) -> bool {
+    let essence = media_type.split(';').next().unwrap_or_default().trim();
+    essence == "application/x-www-form-urlencoded" || essence == "multipart/form-data"
+}
+
+/// The serialization of the examples in the OpenAPI Specification,
+/// e.g. `.blue.black.brown` of an exploded `label` array.
+fn path_serialization(param: &ast::Schema, style: &PathStyle, explode: bool) -> ast::Serialization {
+    // The prefix of a value, and the prefix and the delimiter of an exploded object.
+    let (prefix, object_prefix, object_delimiter) = match style {
+        PathStyle::Simple => (String::new(), "", ","),
+        PathStyle::Label => (".".to_string(), ".", "."),
+        PathStyle::Matrix => (format!(";{}=", param.name), ";", ";"),
+    };
+
+    match &param.ty {
+        ast::Type::Array { .. } => {
+            // An exploded array repeats the prefix, e.g. `;id=3;id=4`, except for `simple`.
+            let delimiter = match style {
+                PathStyle::Label | PathStyle::Matrix if explode => prefix.clone(),
+                _ => ",".to_string(),
+            };
+            ast::Serialization::Delimited { prefix, delimiter }
+        }
+        ty if is_object_type(ty) => {
+            if explode {
+                ast::Serialization::KeyValues {
+                    prefix: object_prefix.to_string(),
+                    delimiter: object_delimiter.to_string(),
+                }
+            } else {
+                ast::Serialization::DelimitedPairs {
+                    prefix,
+                    delimiter: ",".to_string(),
+                }
+            }
+        }
+        _ if prefix.is_empty() => ast::Serialization::Native,
+        _ => ast::Serialization::Prefixed { prefix },
+    }
+}
+
+/// Named schemas are objects, since only objects are emitted as definitions.
+fn is_object_type(ty: &ast::Type) -> bool {
+    matches!(ty, ast::Type::Object { .. } | ast::Type::Ref { .. })
+}
+
+/// `enum` without `null`, which is allowed by `nullable` instead.
+fn enum_validate<T: serde::Serialize>(enumeration: &[Option<T>]) -> Option<ast::Validate> {
+    let values = enumeration
+        .iter()
+        .flatten()
+        .map(|value| serde_json::to_value(value).unwrap())
+        .collect::<Vec<_>>();
+    if values.is_empty() {
+        None
+    } else {
+        Some(ast::Validate::Enum(values))
+    }
+}
+
+/// Returns the `(min, max)` range representable by the integer format.
+/// The range of `int64` is only enforced if `int64_bounds` is set.
+fn integer_format_bounds(
+    format: &VariantOrUnknownOrEmpty<IntegerFormat>,
+    int64_bounds: bool,
+) -> Option<(i64, i64)> {
+    match format {
+        VariantOrUnknownOrEmpty::Item(IntegerFormat::Int32) => {
+            Some((i32::MIN.into(), i32::MAX.into()))
+        }
+        VariantOrUnknownOrEmpty::Item(IntegerFormat::Int64) if int64_bounds => {
+            Some((i64::MIN, i64::MAX))
+        }
+        VariantOrUnknownOrEmpty::Item(IntegerFormat::Int64)
+        | VariantOrUnknownOrEmpty::Unknown(_)
+        | VariantOrUnknownOrEmpty::Empty => None,
+    }
+}
+
+/// `format: decimal` or `x-ruby-type: decimal` is validated as a `BigDecimal` instead of a `Float`.
+fn is_decimal(number: &NumberType, schema_data: &SchemaData) -> bool {
+    let is_decimal_format =
+        matches!(&number.format, VariantOrUnknownOrEmpty::Unknown(format) if format == "decimal");
+    let is_decimal_ruby_type = schema_data
+        .extensions
+        .get("x-ruby-type")
+        .and_then(|ruby_type| ruby_type.as_str())
+        == Some("decimal");
+
+    is_decimal_format || is_decimal_ruby_type
+}
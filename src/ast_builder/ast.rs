@@ -1,5 +1,14 @@
 pub struct RootSchema {
     pub path_items: Vec<PathItem>,
+    /// Schemas that are referenced by name, ordered so that dependencies come first.
+    pub named_schemas: Vec<NamedSchema>,
+    /// Messages of an AsyncAPI document.
+    pub messages: Vec<Message>,
+}
+
+pub struct NamedSchema {
+    pub name: String,
+    pub ty: Type,
 }
 
 pub struct PathItem {
@@ -10,13 +19,62 @@ pub struct PathItem {
 pub struct Operation {
     pub id: Option<String>,
     pub queries: Vec<Schema>,
+    pub path_params: Vec<Schema>,
+    pub headers: Vec<Schema>,
+    pub request_body: Option<Body>,
+    pub responses: Vec<Response>,
+}
+
+/// A JSON or form body whose root is an object.
+pub struct Body {
+    pub properties: Vec<Property>,
+    /// Whether the body is a form, whose values are strings to be coerced like parameters.
+    pub form: bool,
+}
+
+pub struct Message {
+    pub name: String,
+    pub body: Body,
+}
+
+pub struct Response {
+    pub status: String,
+    pub headers: Vec<Schema>,
+    pub body: Option<Body>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Schema {
     pub ty: Type,
     pub required: bool,
+    /// Whether `null` is allowed in addition to the type.
+    pub nullable: bool,
     pub name: String,
+    pub default: Option<serde_json::Value>,
+    pub serialization: Serialization,
+    /// Whether an empty value such as `?q=` is valid.
+    pub allow_empty_value: bool,
+    pub deprecated: bool,
+}
+
+/// How a parameter is written in a request, if it is not parsed into its shape by Rack.
+/// `prefix` is written before the value, e.g. `.` of `label` and `;id=` of `matrix`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Serialization {
+    /// Parsed by Rack, e.g. `ids[]=1&ids[]=2` or `filter[name]=x`.
+    Native,
+    /// A value after the prefix, e.g. `.5`.
+    Prefixed { prefix: String },
+    /// An array joined with the delimiter, e.g. `ids=1,2,3`.
+    Delimited { prefix: String, delimiter: String },
+    /// Keys and values of an object joined with the delimiter, e.g. `color=R,100,G,200`.
+    DelimitedPairs { prefix: String, delimiter: String },
+    /// `key=value` of an object joined with the delimiter, e.g. `R=100,G=200`.
+    KeyValues { prefix: String, delimiter: String },
+    /// A JSON text of `content`, e.g. `filter={"name":"x"}`.
+    Json,
+    /// The properties of an object as keys of the query, e.g. `R=100&G=200`.
+    Exploded { keys: Vec<String> },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -29,6 +87,13 @@ pub enum Validate {
     MinLength(usize),
     MaxItems(usize),
     MinItems(usize),
+    UniqueItems,
+    MaxDecimal(String),
+    MinDecimal(String),
+    ExclusiveMaxDecimal(String),
+    ExclusiveMinDecimal(String),
+    /// One of the values, from `enum` or `const`.
+    Enum(Vec<serde_json::Value>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -39,6 +104,9 @@ pub enum Type {
     Number {
         validates: Vec<Validate>,
     },
+    Decimal {
+        validates: Vec<Validate>,
+    },
     String {
         validates: Vec<Validate>,
     },
@@ -51,11 +119,22 @@ pub enum Type {
         validates: Vec<Validate>,
         properties: Vec<Property>,
     },
+    /// A reference to a named schema.
+    /// It is lazy if the named schema is not defined yet where it is referenced.
+    Ref {
+        name: String,
+        lazy: bool,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Property {
     pub required: bool,
+    /// Whether `null` is allowed in addition to the type.
+    pub nullable: bool,
     pub key: String,
     pub value: Type,
+    pub default: Option<serde_json::Value>,
+    pub read_only: bool,
+    pub write_only: bool,
 }
@@ -1,201 +1,365 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::Path,
+};
 
 use anyhow::Result;
 
-use openapiv3::{OpenAPI, Parameter, PathItem, Schema};
+use openapiv3::ReferenceOr;
+use percent_encoding::percent_decode_str;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 use url::Url;
 
-use crate::{select_file_type, SupportFileType};
+use crate::{
+    frontend::{Dialect, NumberLiterals},
+    loader::check_document_size,
+    select_file_type, DocumentLoader, ResolutionPolicy, SupportFileType,
+};
 
+/// `[file]#fragment`, where the fragment is a JSON Pointer into the document.
+/// A reference without a file refers to the root document,
+/// and a reference without a fragment refers to the whole file.
+/// The file is resolved against the base URI of the referencing document(RFC 3986).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-enum Reference {
-    Local { path: String },
-    LocalFile(FileKey),
+struct Reference {
+    file: Option<FileKey>,
+    /// The fragment as written, still percent-encoded.
+    fragment: String,
 }
 impl Reference {
-    fn new(reference: &str) -> Result<Self> {
-        if reference.starts_with('#') {
-            let paths = reference.split('#');
-            let paths = paths.collect::<Vec<_>>();
-            if paths.len() != 2 {
-                anyhow::bail!("Failed to parse reference.(reference: {reference})");
-            }
-            let path = paths[1];
-
-            Ok(Self::Local {
-                path: path.to_string(),
-            })
+    fn new(reference: &str, base: &Url) -> Result<Self> {
+        let (file, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        if fragment.contains('#') {
+            anyhow::bail!("Failed to parse reference.(reference: {reference})");
+        }
+        let file = if file.is_empty() {
+            None
         } else {
-            Ok(Self::LocalFile(FileKey::new(reference)?))
+            Some(FileKey::new(file, base)?)
+        };
+
+        Ok(Self {
+            file,
+            fragment: fragment.to_string(),
+        })
+    }
+
+    fn pointer(&self) -> Result<String> {
+        // The pointer is percent-encoded in a URI fragment.
+        Ok(percent_decode_str(&self.fragment)
+            .decode_utf8()?
+            .to_string())
+    }
+}
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{file}")?;
+        }
+        if self.file.is_none() || !self.fragment.is_empty() {
+            write!(f, "#{}", self.fragment)?;
         }
+
+        Ok(())
     }
 }
 
 #[derive(Debug)]
-pub(super) struct ReferenceDatabase<'a> {
-    local: &'a OpenAPI,
-    path_item_by_file: HashMap<FileKey, PathItem>,
-    parameter_by_file: HashMap<FileKey, Parameter>,
-    schema_by_file: HashMap<FileKey, Schema>,
+pub(crate) struct ReferenceDatabase<'a> {
+    /// The root document as JSON, so that a pointer can refer to any node in it.
+    local: Value,
+    /// The base URI of the root document.
+    base: Url,
+    /// External documents, whose `$ref`s are rewritten to be absolute.
+    documents: HashMap<FileKey, Value>,
+    loader: &'a dyn DocumentLoader,
+    policy: &'a ResolutionPolicy,
+    /// The dialect of the root document, which external documents are converted from.
+    dialect: Dialect,
+    /// What is dropped by converting external documents.
+    diagnostics: Vec<String>,
+    /// Numeric literals of the root document and the external documents loaded so far.
+    number_literals: NumberLiterals,
+    /// Files which the local documents refer to, since only a local document may refer to a file.
+    local_file_targets: HashSet<Url>,
 }
 impl<'a> ReferenceDatabase<'a> {
-    pub(super) fn new(local: &'a OpenAPI) -> Self {
+    pub(crate) fn new(
+        local: Value,
+        base: &Url,
+        loader: &'a dyn DocumentLoader,
+        policy: &'a ResolutionPolicy,
+        dialect: Dialect,
+    ) -> Self {
+        let mut local_file_targets = HashSet::new();
+        if base.scheme() == "file" {
+            collect_file_targets(&local, base, &mut local_file_targets);
+        }
+
         Self {
             local,
-            path_item_by_file: HashMap::new(),
-            parameter_by_file: HashMap::new(),
-            schema_by_file: HashMap::new(),
+            base: base.clone(),
+            loader,
+            policy,
+            documents: HashMap::new(),
+            dialect,
+            diagnostics: Vec::new(),
+            number_literals: NumberLiterals::default(),
+            local_file_targets,
         }
     }
 
-    pub(super) fn resolve_path_item(&mut self, reference: &str) -> Result<&PathItem> {
-        let reference = FileKey::new(reference)?;
+    /// Keeps the numeric literals of the root document, which is parsed before.
+    pub(crate) fn with_number_literals(mut self, number_literals: NumberLiterals) -> Self {
+        self.number_literals = number_literals;
+        self
+    }
 
-        let entry = self.path_item_by_file.entry(reference);
-        Ok(entry.or_insert_with_key(|reference| {
-            let file_type = reference.file_type().unwrap();
-            let file = reference.read_content();
+    /// The number as it is written in the documents.
+    pub(crate) fn number_literal(&self, number: f64) -> Result<String> {
+        self.number_literals.literal(number)
+    }
 
-            let item: PathItem = match file_type {
-                SupportFileType::Json => serde_json::from_str(&file).unwrap(),
-                SupportFileType::Yaml => serde_yaml::from_str(&file).unwrap(),
-            };
-            item
-        }))
+    /// Returns what is dropped by converting the external documents loaded so far.
+    pub(crate) fn take_diagnostics(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.diagnostics)
     }
 
-    pub(super) fn resolve_parameter(&mut self, reference: &str) -> Result<&Parameter> {
-        let reference = Reference::new(reference)?;
+    /// Returns the reference in the form used by the documents in the database,
+    /// so that the same schema is always referred to by the same string.
+    pub(crate) fn normalize_reference(&self, reference: &str) -> Result<String> {
+        Ok(Reference::new(reference, &self.base)?.to_string())
+    }
 
-        match reference {
-            Reference::Local { path } => {
-                let paths = path
-                    .split('/')
-                    // #/components/parameters/SomeParameter
-                    //  ^skip first slash
-                    .skip(1)
-                    .collect::<Vec<_>>();
-                if paths.len() != 3 {
-                    anyhow::bail!("Invalid path.");
-                }
-                if paths[0] != "components" {
-                    anyhow::bail!("Invalid path.");
-                }
-                if paths[1] != "parameters" {
-                    anyhow::bail!("Invalid path.");
-                }
+    /// Resolves a reference to any object that can be referenced,
+    /// such as `PathItem`, `Schema`, `Parameter`, `RequestBody`, `Response` and `Header`.
+    pub(super) fn resolve<T: DeserializeOwned>(&mut self, reference: &str) -> Result<T> {
+        self.resolve_with_visited(reference, &mut Vec::new())
+    }
 
-                let parameter_name = paths[2];
-                let parameter = &self.local.components.as_ref().unwrap().parameters[parameter_name];
-                match parameter {
-                    openapiv3::ReferenceOr::Reference { reference } => {
-                        // ToDo: Stop generating the same parameters recursively.
-                        self.resolve_parameter(reference)
-                    }
-                    openapiv3::ReferenceOr::Item(item) => Ok(item),
+    /// Returns the node which the reference points to as it is, without following it further.
+    /// `$ref`s in the node of an external document are absolute.
+    pub(crate) fn find_node(&mut self, reference: &str) -> Result<Value> {
+        let reference = Reference::new(reference, &self.base)?;
+        Ok(self.find(&reference)?.clone())
+    }
+
+    /// Follows references until an item is found.
+    fn resolve_with_visited<T: DeserializeOwned>(
+        &mut self,
+        reference: &str,
+        visited: &mut Vec<String>,
+    ) -> Result<T> {
+        let reference = Reference::new(reference, &self.base)?;
+        check_circular_reference(&reference.to_string(), visited)?;
+
+        let node = self.find(&reference)?.clone();
+        match serde_json::from_value::<ReferenceOr<T>>(node)? {
+            ReferenceOr::Reference { reference } => self.resolve_with_visited(&reference, visited),
+            ReferenceOr::Item(item) => Ok(item),
+        }
+    }
+
+    fn find(&mut self, reference: &Reference) -> Result<&Value> {
+        let pointer = reference.pointer()?;
+        let document = match &reference.file {
+            Some(file) => self.load(file)?,
+            None => &self.local,
+        };
+
+        find_pointer(document, &pointer)
+    }
+
+    /// Loads the document once, and returns the cached one after that.
+    fn load(&mut self, file: &FileKey) -> Result<&Value> {
+        if !self.documents.contains_key(file) {
+            self.check_policy(file.url())?;
+            let (mut document, number_literals) = file.read_document(self.loader, self.policy)?;
+            self.number_literals.extend(number_literals);
+            absolutize_references(&mut document, file.url());
+            if file.url().scheme() == "file" {
+                collect_file_targets(&document, file.url(), &mut self.local_file_targets);
+            }
+            let diagnostics = self
+                .dialect
+                .convert_external(&mut document, file.url().as_str());
+            self.diagnostics.extend(diagnostics);
+            self.documents.insert(file.clone(), document);
+        }
+
+        Ok(&self.documents[file])
+    }
+
+    /// Fails if the document is not allowed to be loaded.
+    fn check_policy(&self, url: &Url) -> Result<()> {
+        // Whatever the policy is, so that a remote document can not read local files.
+        if url.scheme() == "file" && !self.local_file_targets.contains(url) {
+            anyhow::bail!("A file can only be referred to from a local document.(url: {url})");
+        }
+        let policy = self.policy;
+        if !policy
+            .allowed_schemes
+            .iter()
+            .any(|scheme| scheme == url.scheme())
+        {
+            anyhow::bail!("The scheme is not allowed.(url: {url})");
+        }
+        if url.scheme() == "file" {
+            if let Some(root_dir) = &policy.root_dir {
+                if !is_inside(root_dir, url)? {
+                    anyhow::bail!(
+                        "The file is outside of the root directory.(url: {url}, root: {})",
+                        root_dir.display()
+                    );
                 }
             }
-            Reference::LocalFile(file_key) => {
-                let entry = self.parameter_by_file.entry(file_key);
-                Ok(entry.or_insert_with_key(|reference| {
-                    let file_type = reference.file_type().unwrap();
-                    let file = reference.read_content();
-
-                    let item: Parameter = match file_type {
-                        SupportFileType::Json => serde_json::from_str(&file).unwrap(),
-                        SupportFileType::Yaml => serde_yaml::from_str(&file).unwrap(),
-                    };
-                    item
-                }))
+        } else if let Some(allowed_hosts) = &policy.allowed_hosts {
+            let host = url.host_str().unwrap_or_default();
+            if !allowed_hosts.iter().any(|allowed| allowed == host) {
+                anyhow::bail!("The host is not allowed.(url: {url})");
             }
         }
+        if let Some(max_documents) = policy.max_documents {
+            if self.documents.len() >= max_documents {
+                anyhow::bail!("Too many documents.(url: {url}, max: {max_documents})");
+            }
+        }
+
+        Ok(())
     }
+}
 
-    pub(super) fn resolve_schema(&mut self, reference: &str) -> Result<&Schema> {
-        let reference = Reference::new(reference)?;
+/// Whether the file is in the directory, after following symbolic links if they exist.
+fn is_inside(dir: &Path, url: &Url) -> Result<bool> {
+    let Ok(path) = url.to_file_path() else {
+        anyhow::bail!("Invalid file path.(url: {url})");
+    };
+    let dir = std::path::absolute(dir)?;
+    let dir = dir.canonicalize().unwrap_or(dir);
+    // `..` is already removed from the URL.
+    let path = path.canonicalize().unwrap_or(path);
 
-        match reference {
-            Reference::Local { path } => {
-                let paths = path
-                    .split('/')
-                    // #/components/parameters/SomeParameter
-                    //  ^skip first slash
-                    .skip(1)
-                    .collect::<Vec<_>>();
-                if paths.len() != 3 {
-                    anyhow::bail!("Invalid path.");
-                }
-                if paths[0] != "components" {
-                    anyhow::bail!("Invalid path.");
-                }
-                if paths[1] != "schemas" {
-                    anyhow::bail!("Invalid path.");
+    Ok(path.starts_with(dir))
+}
+
+/// Finds the node of a JSON Pointer(RFC 6901) in the document.
+fn find_pointer<'a>(document: &'a Value, pointer: &str) -> Result<&'a Value> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        anyhow::bail!("Invalid JSON Pointer.(pointer: {pointer})");
+    }
+    match document.pointer(pointer) {
+        Some(node) => Ok(node),
+        None => anyhow::bail!("Not found.(pointer: {pointer})"),
+    }
+}
+
+/// Rewrites the references in an external document to absolute ones against its URI,
+/// so that they are resolved in the same way after they are copied out of the document.
+fn absolutize_references(value: &mut Value, base: &Url) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Value::String(reference) if key == "$ref" => {
+                        if let Ok(absolute) = base.join(reference) {
+                            *reference = absolute.to_string();
+                        }
+                    }
+                    value => absolutize_references(value, base),
                 }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                absolutize_references(value, base);
+            }
+        }
+        _ => (),
+    }
+}
 
-                let schema_name = paths[2];
-                let schema = &self.local.components.as_ref().unwrap().schemas[schema_name];
-                match schema {
-                    openapiv3::ReferenceOr::Reference { reference } => {
-                        // ToDo: Stop generating the same parameters recursively.
-                        self.resolve_schema(reference)
+/// Collects the files which the references in the document refer to, without their fragments.
+fn collect_file_targets(value: &Value, base: &Url, targets: &mut HashSet<Url>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match value {
+                    Value::String(reference) if key == "$ref" => {
+                        if let Ok(mut target) = base.join(reference) {
+                            target.set_fragment(None);
+                            if target.scheme() == "file" {
+                                targets.insert(target);
+                            }
+                        }
                     }
-                    openapiv3::ReferenceOr::Item(item) => Ok(item),
+                    value => collect_file_targets(value, base, targets),
                 }
             }
-            Reference::LocalFile(file_key) => {
-                let entry = self.schema_by_file.entry(file_key);
-                Ok(entry.or_insert_with_key(|reference| {
-                    let file_type = reference.file_type().unwrap();
-                    let file = reference.read_content();
-
-                    let item: Schema = match file_type {
-                        SupportFileType::Json => serde_json::from_str(&file).unwrap(),
-                        SupportFileType::Yaml => serde_yaml::from_str(&file).unwrap(),
-                    };
-                    item
-                }))
+        }
+        Value::Array(values) => {
+            for value in values {
+                collect_file_targets(value, base, targets);
             }
         }
+        _ => (),
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-enum FileKey {
-    Local(PathBuf),
-    Remote(Url),
+/// Fails if the reference is already being followed, e.g. `A: $ref B` and `B: $ref A`.
+fn check_circular_reference(reference: &str, visited: &mut Vec<String>) -> Result<()> {
+    if visited.iter().any(|visited| visited == reference) {
+        anyhow::bail!(
+            "Circular reference.(references: {} -> {reference})",
+            visited.join(" -> ")
+        );
+    }
+    visited.push(reference.to_string());
+
+    Ok(())
 }
+
+/// The URL of an external document.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FileKey(Url);
 impl FileKey {
-    fn new(path: &str) -> Result<Self> {
-        if path.starts_with("https://") || path.starts_with("http://") {
-            Ok(Self::Remote(url::Url::parse(path)?))
-        } else {
-            Ok(Self::Local(std::fs::canonicalize(path)?))
-        }
+    fn new(path: &str, base: &Url) -> Result<Self> {
+        Ok(Self(base.join(path)?))
+    }
+
+    fn url(&self) -> &Url {
+        &self.0
     }
 
     fn file_type(&self) -> Result<SupportFileType> {
-        match self {
-            FileKey::Local(path) => select_file_type(path),
-            FileKey::Remote(url) => {
-                let url = url.to_string();
-                if url.ends_with(".json") {
-                    Ok(SupportFileType::Json)
-                } else if url.ends_with(".yaml") || url.ends_with(".yml") {
-                    Ok(SupportFileType::Yaml)
-                } else {
-                    anyhow::bail!(format!("Unknown file type. url: {url}"))
-                }
-            }
-        }
+        select_file_type(&Path::new(self.0.path()))
     }
 
-    fn read_content(&self) -> String {
-        match self {
-            FileKey::Local(path) => std::fs::read_to_string(path).unwrap(),
-            FileKey::Remote(url) => reqwest::blocking::get(url.to_string())
-                .unwrap()
-                .text()
-                .unwrap(),
+    fn read_document(
+        &self,
+        loader: &dyn DocumentLoader,
+        policy: &ResolutionPolicy,
+    ) -> Result<(Value, NumberLiterals)> {
+        let content = loader.load(self.url())?;
+        // The default loaders reject a large document before reading it, but other loaders may not.
+        if let Some(max_document_size) = policy.max_document_size {
+            check_document_size(self.url(), content.len() as u64, max_document_size)?;
         }
+        let document = match self.file_type()? {
+            SupportFileType::Json => serde_json::from_str(&content)?,
+            // Via YAML values, because YAML allows non-string keys such as status codes.
+            SupportFileType::Yaml => {
+                let document: serde_yaml::Value = serde_yaml::from_str(&content)?;
+                serde_json::to_value(document)?
+            }
+        };
+
+        Ok((document, NumberLiterals::scan(&content)))
+    }
+}
+impl fmt::Display for FileKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.url())
     }
 }
@@ -0,0 +1,328 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use percent_encoding::percent_decode_str;
+use serde_json::{json, Map, Value};
+use url::Url;
+
+use crate::{
+    ast_builder::reference_db::ReferenceDatabase, frontend::Dialect, Config, DocumentLoader,
+};
+
+pub struct BundleResult {
+    pub document: Value,
+    pub errors: Vec<String>,
+}
+
+/// Copies the documents which the root document refers to into the root document.
+/// A reference to an object which can be a component is replaced with a reference to a new component,
+/// and the others, such as a path item, are replaced with the object itself.
+/// References in the root document to itself are kept as they are.
+pub fn bundle(
+    root: Value,
+    base: &Url,
+    config: &Config,
+    loader: &dyn DocumentLoader,
+) -> BundleResult {
+    let mut bundler = Bundler::new(&root, base, config, loader);
+    let mut document = root;
+    bundler.visit(&mut document, Node::Document);
+
+    let errors = bundler.errors;
+    if !bundler.components.is_empty() {
+        let components = document
+            .as_object_mut()
+            .unwrap()
+            .entry("components")
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .unwrap();
+        for (section, bundled) in bundler.components {
+            let section = components
+                .entry(section)
+                .or_insert_with(|| json!({}))
+                .as_object_mut()
+                .unwrap();
+            if let Value::Object(bundled) = bundled {
+                section.extend(bundled);
+            }
+        }
+    }
+
+    BundleResult { document, errors }
+}
+
+/// Sections of `components` where a bundled object is put.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Section {
+    Schemas,
+    Parameters,
+    RequestBodies,
+    Responses,
+    Headers,
+    Examples,
+}
+impl Section {
+    const ALL: [Section; 6] = [
+        Section::Schemas,
+        Section::Parameters,
+        Section::RequestBodies,
+        Section::Responses,
+        Section::Headers,
+        Section::Examples,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            Section::Schemas => "schemas",
+            Section::Parameters => "parameters",
+            Section::RequestBodies => "requestBodies",
+            Section::Responses => "responses",
+            Section::Headers => "headers",
+            Section::Examples => "examples",
+        }
+    }
+}
+
+/// What a node of the document is, which decides where a `$ref` at the node is bundled to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    Document,
+    /// Values which are not OpenAPI objects, such as examples and extensions.
+    Data,
+    PathItems,
+    PathItem,
+    Components(Section),
+    Component(Section),
+    /// Maps of a schema whose keys are names, such as `properties`, so that every value is a schema.
+    SchemaMap,
+}
+impl Node {
+    fn child(self, key: &str) -> Node {
+        match self {
+            Node::Data => Node::Data,
+            Node::PathItems => Node::PathItem,
+            Node::Components(section) => Node::Component(section),
+            Node::SchemaMap => Node::Component(Section::Schemas),
+            Node::Component(Section::Schemas) => match key {
+                "properties" | "patternProperties" | "dependentSchemas" | "$defs"
+                | "definitions" => Node::SchemaMap,
+                "example" | "default" | "enum" => Node::Data,
+                _ => Node::Component(Section::Schemas),
+            },
+            _ => match key {
+                "schema" => Node::Component(Section::Schemas),
+                "schemas" => Node::Components(Section::Schemas),
+                "parameters" => Node::Components(Section::Parameters),
+                "requestBody" => Node::Component(Section::RequestBodies),
+                "requestBodies" => Node::Components(Section::RequestBodies),
+                "responses" => Node::Components(Section::Responses),
+                "headers" => Node::Components(Section::Headers),
+                "examples" => Node::Components(Section::Examples),
+                "paths" => Node::PathItems,
+                "example" | "value" => Node::Data,
+                key if key.starts_with("x-") => Node::Data,
+                _ => Node::Document,
+            },
+        }
+    }
+
+    fn element(self) -> Node {
+        match self {
+            Node::Components(section) => Node::Component(section),
+            node => node,
+        }
+    }
+}
+
+struct Bundler<'a> {
+    db: ReferenceDatabase<'a>,
+    base: Url,
+    /// Names used in each section, by the root document or by bundled objects.
+    names: HashMap<Section, HashSet<String>>,
+    /// Local references of bundled objects, by their normalized references.
+    bundled: HashMap<String, String>,
+    /// Bundled objects by section, in the order they are found.
+    components: Map<String, Value>,
+    /// References being inlined, to detect circular references.
+    inlining: Vec<String>,
+    errors: Vec<String>,
+}
+impl<'a> Bundler<'a> {
+    fn new(root: &Value, base: &Url, config: &'a Config, loader: &'a dyn DocumentLoader) -> Self {
+        let names = Section::ALL
+            .into_iter()
+            .map(|section| {
+                let names = root
+                    .pointer(&format!("/components/{}", section.key()))
+                    .and_then(Value::as_object)
+                    .map(|objects| objects.keys().cloned().collect())
+                    .unwrap_or_default();
+                (section, names)
+            })
+            .collect();
+
+        Self {
+            // Documents are bundled as they are written.
+            db: ReferenceDatabase::new(
+                root.clone(),
+                base,
+                loader,
+                &config.resolution,
+                Dialect::OpenApi30,
+            ),
+            base: base.clone(),
+            names,
+            bundled: HashMap::new(),
+            components: Map::new(),
+            inlining: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn visit(&mut self, value: &mut Value, node: Node) {
+        if node == Node::Data {
+            return;
+        }
+        match value {
+            Value::Object(object) => {
+                if let Some(Value::String(reference)) = object.get("$ref") {
+                    let reference = reference.clone();
+                    if let Some(bundled) = self.bundle_reference(&reference, node) {
+                        *value = bundled;
+                    }
+                    return;
+                }
+                for (key, child) in object.iter_mut() {
+                    self.visit(child, node.child(key));
+                }
+            }
+            Value::Array(values) => {
+                for child in values {
+                    self.visit(child, node.element());
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Returns what replaces the reference, or `None` to keep it.
+    fn bundle_reference(&mut self, reference: &str, node: Node) -> Option<Value> {
+        let result = self
+            .db
+            .normalize_reference(reference)
+            .and_then(|normalized| {
+                // `$ref`s in external documents are absolute, even if they refer to the root document.
+                if let Some(fragment) = normalized
+                    .strip_prefix(self.base.as_str())
+                    .filter(|fragment| fragment.starts_with('#'))
+                {
+                    return Ok(Some(json!({ "$ref": fragment })));
+                }
+                if normalized.starts_with('#') {
+                    return Ok(None);
+                }
+                match node {
+                    Node::Component(section) => {
+                        self.bundle_component(normalized, section).map(Some)
+                    }
+                    node => self.inline(normalized, node).map(Some),
+                }
+            });
+        match result {
+            Ok(bundled) => bundled,
+            Err(err) => {
+                self.errors.push(format!(
+                    "Failed to bundle reference. reference: {reference}, error: {err}"
+                ));
+                None
+            }
+        }
+    }
+
+    fn bundle_component(&mut self, normalized: String, section: Section) -> anyhow::Result<Value> {
+        if let Some(local) = self.bundled.get(&normalized) {
+            return Ok(json!({ "$ref": local }));
+        }
+        let mut target = self.db.find_node(&normalized)?;
+
+        // Named before the target is visited, so that a recursive reference refers to the same component.
+        let name = self.component_name(section, &normalized);
+        let local = format!("#/components/{}/{name}", section.key());
+        self.bundled.insert(normalized, local.clone());
+        let bundled = self
+            .components
+            .entry(section.key())
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .unwrap();
+        bundled.insert(name.clone(), Value::Null);
+
+        self.visit(&mut target, Node::Component(section));
+        self.components[section.key()][&name] = target;
+
+        Ok(json!({ "$ref": local }))
+    }
+
+    fn inline(&mut self, normalized: String, node: Node) -> anyhow::Result<Value> {
+        if self.inlining.contains(&normalized) {
+            anyhow::bail!(
+                "Circular reference.(references: {} -> {normalized})",
+                self.inlining.join(" -> ")
+            );
+        }
+        let mut target = self.db.find_node(&normalized)?;
+
+        self.inlining.push(normalized);
+        self.visit(&mut target, node);
+        self.inlining.pop();
+
+        Ok(target)
+    }
+
+    /// The last token of the JSON Pointer, or the file name without its extension,
+    /// with a number appended if the name is already used.
+    fn component_name(&mut self, section: Section, normalized: &str) -> String {
+        let (file, fragment) = normalized.split_once('#').unwrap_or((normalized, ""));
+        let token = fragment.rsplit('/').next().unwrap_or_default();
+        let name = if token.is_empty() {
+            let file_name = file.rsplit('/').next().unwrap_or_default();
+            Path::new(file_name)
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()
+        } else {
+            percent_decode_str(token)
+                .decode_utf8_lossy()
+                .replace("~1", "/")
+                .replace("~0", "~")
+        };
+        // Component names must match `^[a-zA-Z0-9\.\-_]+$`.
+        let mut name: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        if name.is_empty() {
+            name = "Component".to_string();
+        }
+
+        let names = self.names.entry(section).or_default();
+        let mut unique = name.clone();
+        let mut number = 2;
+        while !names.insert(unique.clone()) {
+            unique = format!("{name}_{number}");
+            number += 1;
+        }
+
+        unique
+    }
+}
@@ -5,19 +5,295 @@ use crate::ir_builder::ir;
 pub fn generate(defs: &ir::Defs) -> String {
     let mut code = String::new();
 
+    if !defs.coercers.is_empty() {
+        code.push_str(&gen_coercions_module(&defs.coercers));
+        code.push('\n');
+    }
+    if !defs.checkers.is_empty() {
+        code.push_str(&gen_rules_module(&defs.checkers));
+        code.push('\n');
+    }
+    if defs.deprecations {
+        code.push_str(DEPRECATIONS_MODULE);
+        code.push('\n');
+    }
+    if defs.defs.iter().any(|def| !def.defaults.is_empty()) {
+        code.push_str(DEFAULTS_MODULE);
+        code.push('\n');
+    }
+
     for def in &defs.defs {
-        code.push_str(&format!(
-            "{} = {}",
-            gen_def_name(&def.name),
-            gen_schema_class(&def.class)
-        ));
-        code.push_str(&gen_block(&def.block, 0));
+        if def.rules.is_empty() {
+            code.push_str(&format!(
+                "{} = {}",
+                gen_def_name(&def.name),
+                gen_schema_class(&def.class)
+            ));
+            code.push_str(&gen_block(&def.block, 0));
+        } else {
+            code.push_str(&gen_contract(def));
+        }
         code.push('\n');
+        if !def.defaults.is_empty() {
+            code.push_str(&format!(
+                "{}Defaults = {}\n",
+                gen_def_name(&def.name),
+                gen_defaults_literal(&def.defaults, 0)
+            ));
+        }
     }
 
     code
 }
 
+fn gen_coercions_module(coercers: &[ir::Coercer]) -> String {
+    let mut out = "module Coercions\n".to_string();
+    for (index, coercer) in coercers.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        for line in gen_coercer(coercer).lines() {
+            if line.is_empty() {
+                out.push('\n');
+            } else {
+                out.push_str(&format!("{}{line}\n", indent(1)));
+            }
+        }
+    }
+    out.push_str("end\n");
+
+    out
+}
+
+fn gen_coercer(coercer: &ir::Coercer) -> &'static str {
+    match coercer {
+        ir::Coercer::StripPrefix => {
+            r#"# Removes the prefix of the string of the key, e.g. ";id=5" into "5".
+def self.strip_prefix(input, name, prefix)
+  value = input[name]
+  return input unless value.is_a?(String) && value.start_with?(prefix)
+
+  input.merge(name => value.delete_prefix(prefix))
+end"#
+        }
+        ir::Coercer::Split => {
+            r#"# Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+def self.split(input, name, delimiter, prefix: "")
+  value = input[name]
+  return input unless value.is_a?(String) && value.start_with?(prefix)
+
+  input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+end"#
+        }
+        ir::Coercer::SplitPairs => {
+            r#"# Splits the string of the key into a hash, e.g. "R,100,G,200" into { R: "100", G: "200" }.
+# An odd number of elements is left as it is, so that it fails as a hash.
+def self.split_pairs(input, name, delimiter, prefix: "")
+  value = input[name]
+  return input unless value.is_a?(String) && value.start_with?(prefix)
+
+  pairs = value.delete_prefix(prefix).split(delimiter, -1)
+  return input if pairs.size.odd?
+
+  input.merge(name => pairs.each_slice(2).to_h { |key, item| [key.to_sym, item] })
+end"#
+        }
+        ir::Coercer::SplitKeyValues => {
+            r#"# Splits the string of the key into a hash, e.g. "R=100,G=200" into { R: "100", G: "200" }.
+# A pair without "=" is left as it is, so that it fails as a hash.
+def self.split_key_values(input, name, delimiter, prefix: "")
+  value = input[name]
+  return input unless value.is_a?(String) && value.start_with?(prefix)
+
+  pairs = value.delete_prefix(prefix).split(delimiter, -1).map { |pair| pair.split("=", 2) }
+  return input unless pairs.all? { |pair| pair.size == 2 }
+
+  input.merge(name => pairs.to_h { |key, item| [key.to_sym, item] })
+end"#
+        }
+        ir::Coercer::ParseJson => {
+            r#"# Parses the JSON text of the key, e.g. '{"name":"x"}' into { name: "x" }.
+# An invalid text is left as it is, so that it fails validation.
+def self.parse_json(input, name)
+  value = input[name]
+  return input unless value.is_a?(String)
+
+  input.merge(name => JSON.parse(value, symbolize_names: true))
+rescue JSON::ParserError
+  input
+end"#
+        }
+        ir::Coercer::GatherKeys => {
+            r#"# Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+# The key is left as it is if it is given, e.g. as "color[R]=100".
+def self.gather_keys(input, name, keys)
+  return input if input.key?(name) || input.key?(name.to_sym)
+
+  present = input.keys.select { |key| keys.include?(key.to_s) }
+  return input if present.empty?
+
+  input.except(*present).merge(name => input.slice(*present))
+end"#
+        }
+    }
+}
+
+fn gen_rules_module(checkers: &[ir::Checker]) -> String {
+    let mut out = "module Rules\n".to_string();
+    let helpers = std::iter::once(EACH_VALUE).chain(checkers.iter().map(gen_checker));
+    for (index, helper) in helpers.enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        for line in helper.lines() {
+            if line.is_empty() {
+                out.push('\n');
+            } else {
+                out.push_str(&format!("{}{line}\n", indent(1)));
+            }
+        }
+    }
+    out.push_str("end\n");
+
+    out
+}
+
+/// Used by every rule to find the values at its path.
+const EACH_VALUE: &str = r#"# Yields each value at the path with its full path, where :* stands for every element of an array.
+def self.each_value(value, path, value_path = [], &block)
+  return yield(value, value_path) if path.empty?
+
+  key, *rest = path
+  if key == :*
+    return unless value.is_a?(Array)
+
+    value.each_with_index { |item, index| each_value(item, rest, [*value_path, index], &block) }
+  elsif value.is_a?(Hash) && value.key?(key)
+    each_value(value[key], rest, [*value_path, key], &block)
+  end
+end"#;
+
+fn gen_checker(checker: &ir::Checker) -> &'static str {
+    match checker {
+        ir::Checker::UniqueItems => {
+            r#"# Returns the index of the first element that already appeared earlier, or nil.
+def self.first_duplicate_index(array)
+  seen = {}
+  array.each_with_index do |item, index|
+    return index if seen.key?(item)
+
+    seen[item] = true
+  end
+  nil
+end
+
+def self.unique_items(rule, path)
+  each_value(rule.values.data, path) do |value, value_path|
+    next unless value.is_a?(Array)
+
+    index = first_duplicate_index(value)
+    rule.key(value_path).failure("must not contain duplicates, but the item at index #{index} is one") if index
+  end
+end"#
+        }
+        ir::Checker::Forbidden => {
+            r#"def self.forbidden(rule, path, message)
+  each_value(rule.values.data, path) { |_value, value_path| rule.key(value_path).failure(message) }
+end"#
+        }
+        ir::Checker::NestedSchema => {
+            r#"# Validates each hash at the path with the schema, and adds its errors under the path.
+def self.nested_schema(rule, path, schema)
+  each_value(rule.values.data, path) do |value, value_path|
+    next unless value.is_a?(Hash)
+
+    schema.call(value).errors.each { |error| rule.key([*value_path, *error.path]).failure(error.text) }
+  end
+end"#
+        }
+    }
+}
+
+/// A contract of dry-validation, whose rules run with the output of the schema.
+/// It is instantiated so that it is called like a schema.
+fn gen_contract(def: &ir::Def) -> String {
+    let mut out = format!(
+        "{} = Class.new(Dry::Validation::Contract) do\n",
+        gen_def_name(&def.name)
+    );
+    let schema = match def.class {
+        ir::SchemaClass::Params => "params",
+        ir::SchemaClass::Json => "json",
+    };
+    out.push_str(&format!("{}{schema}", indent(1)));
+    out.push_str(&gen_block(&def.block, 1));
+    out.push_str("\n\n");
+    out.push_str(&format!("{}rule do\n", indent(1)));
+    for rule in &def.rules {
+        out.push_str(&format!("{}{}\n", indent(2), gen_rule(rule)));
+    }
+    out.push_str(&format!("{}end\n", indent(1)));
+    out.push_str("end.new");
+
+    out
+}
+
+fn gen_rule(rule: &ir::Rule) -> String {
+    let path = rule
+        .path
+        .iter()
+        .map(|segment| match segment {
+            ir::PathSegment::Key(key) => gen_symbol(key),
+            ir::PathSegment::Each => ":*".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    match &rule.check {
+        ir::Check::UniqueItems => format!("Rules.unique_items(self, [{path}])"),
+        ir::Check::Forbidden(message) => format!(
+            "Rules.forbidden(self, [{path}], {})",
+            gen_string_literal(message)
+        ),
+        ir::Check::NestedSchema(name) => format!(
+            "Rules.nested_schema(self, [{path}], {})",
+            gen_def_name(name)
+        ),
+    }
+}
+
+const DEFAULTS_MODULE: &str = r##"module Defaults
+  # Defaults of the keys of a hash, which are filled only if the hash is given or has a default of its own.
+  Nested = Struct.new(:defaults, :default)
+
+  # Fills the keys missing from the input with their defaults, e.g. `Defaults.apply(params, ShowUserDefaults)`.
+  def self.apply(input, defaults)
+    return input unless input.is_a?(Hash)
+
+    defaults.each_with_object(input.dup) do |(key, default), output|
+      if !default.is_a?(Nested)
+        output[key] = default unless output.key?(key)
+      elsif output.key?(key) || !default.default.nil?
+        output[key] = apply(output.fetch(key, default.default), default.defaults)
+      end
+    end
+  end
+end
+"##;
+
+const DEPRECATIONS_MODULE: &str = r##"module Deprecations
+  class << self
+    # Called with the name of the schema and the deprecated keys which are present, e.g. to log them.
+    attr_accessor :handler
+  end
+  self.handler = ->(schema, keys) { warn("#{schema}: #{keys.join(", ")} deprecated") }
+
+  def self.report(schema, input, keys)
+    present = keys.select { |key| input.key?(key) }
+    handler.call(schema, present) unless present.empty?
+  end
+end
+"##;
+
 fn gen_block(block: &ir::Block, nesting: usize) -> String {
     let mut out = " do\n".to_string();
     for stmt in &block.stmts {
@@ -35,10 +311,18 @@ fn gen_block(block: &ir::Block, nesting: usize) -> String {
 fn gen_stmt(stmt: &ir::Stmt, nesting: usize) -> String {
     match stmt {
         ir::Stmt::Required { name, r#macro } => {
-            format!("required(:{name}){}", gen_macro(r#macro, nesting))
+            format!(
+                "required({}){}",
+                gen_symbol(name),
+                gen_macro(r#macro, nesting)
+            )
         }
         ir::Stmt::Optional { name, r#macro } => {
-            format!("optional(:{name}){}", gen_macro(r#macro, nesting))
+            format!(
+                "optional({}){}",
+                gen_symbol(name),
+                gen_macro(r#macro, nesting)
+            )
         }
         ir::Stmt::Schema { ty, r#macro } => {
             format!(
@@ -47,6 +331,95 @@ fn gen_stmt(stmt: &ir::Stmt, nesting: usize) -> String {
                 gen_macro(r#macro, nesting)
             )
         }
+        ir::Stmt::Coerce(coercions) => gen_coerce(coercions, nesting),
+        ir::Stmt::GatherKeys(gathered_keys) => gen_gather_keys(gathered_keys, nesting),
+        ir::Stmt::ReportDeprecated { def_name, names } => {
+            gen_report_deprecated(def_name, names, nesting)
+        }
+        ir::Stmt::Comment(comment) => format!("# {comment}"),
+    }
+}
+
+/// Keys are already symbols, since the key coercer runs before the value coercer.
+fn gen_coerce(coercions: &[(String, ir::Coercion)], nesting: usize) -> String {
+    let mut out = "before(:value_coercer) do |result|\n".to_string();
+    out.push_str(&format!("{}input = result.to_h\n", indent(nesting + 1)));
+    for (name, coercion) in coercions {
+        let mut args = vec!["input".to_string(), gen_symbol(name)];
+        match coercion {
+            ir::Coercion::StripPrefix(prefix) => args.push(gen_string_literal(prefix)),
+            ir::Coercion::Split { prefix, delimiter }
+            | ir::Coercion::SplitPairs { prefix, delimiter }
+            | ir::Coercion::SplitKeyValues { prefix, delimiter } => {
+                args.push(gen_string_literal(delimiter));
+                if !prefix.is_empty() {
+                    args.push(format!("prefix: {}", gen_string_literal(prefix)));
+                }
+            }
+            ir::Coercion::ParseJson => (),
+        }
+        out.push_str(&format!(
+            "{}input = Coercions.{}({})\n",
+            indent(nesting + 1),
+            gen_coercer_name(&coercion.coercer()),
+            args.join(", ")
+        ));
+    }
+    out.push_str(&format!("{}input\n", indent(nesting + 1)));
+    out.push_str(&format!("{}end", indent(nesting)));
+
+    out
+}
+
+/// Keys are still as they are given, e.g. strings of Rack.
+fn gen_gather_keys(gathered_keys: &[(String, Vec<String>)], nesting: usize) -> String {
+    let mut out = "before(:key_coercer) do |result|\n".to_string();
+    out.push_str(&format!("{}input = result.to_h\n", indent(nesting + 1)));
+    for (name, keys) in gathered_keys {
+        let keys = keys
+            .iter()
+            .map(|key| gen_string_literal(key))
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!(
+            "{}input = Coercions.gather_keys(input, {}, [{keys}])\n",
+            indent(nesting + 1),
+            gen_string_literal(name)
+        ));
+    }
+    out.push_str(&format!("{}input\n", indent(nesting + 1)));
+    out.push_str(&format!("{}end", indent(nesting)));
+
+    out
+}
+
+fn gen_report_deprecated(def_name: &str, names: &[String], nesting: usize) -> String {
+    let names = names
+        .iter()
+        .map(|name| gen_symbol(name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut out = "before(:value_coercer) do |result|\n".to_string();
+    out.push_str(&format!("{}input = result.to_h\n", indent(nesting + 1)));
+    out.push_str(&format!(
+        "{}Deprecations.report({}, input, [{names}])\n",
+        indent(nesting + 1),
+        gen_string_literal(&gen_def_name(def_name))
+    ));
+    out.push_str(&format!("{}input\n", indent(nesting + 1)));
+    out.push_str(&format!("{}end", indent(nesting)));
+
+    out
+}
+
+fn gen_coercer_name(coercer: &ir::Coercer) -> &'static str {
+    match coercer {
+        ir::Coercer::StripPrefix => "strip_prefix",
+        ir::Coercer::Split => "split",
+        ir::Coercer::SplitPairs => "split_pairs",
+        ir::Coercer::SplitKeyValues => "split_key_values",
+        ir::Coercer::ParseJson => "parse_json",
+        ir::Coercer::GatherKeys => "gather_keys",
     }
 }
 
@@ -54,16 +427,21 @@ fn gen_macro(r#macro: &ir::Macro, nesting: usize) -> String {
     match r#macro {
         ir::Macro::Value {
             ty,
+            presence,
             validates,
             macro_or_block,
         } => {
-            const LITERAL: &str = ".value";
+            let literal = match presence {
+                ir::Presence::Value => ".value",
+                ir::Presence::Filled => ".filled",
+                ir::Presence::Maybe => ".maybe",
+            };
             let mut out = if validates.is_empty() {
-                format!("{}(:{})", LITERAL, gen_type_spec(ty))
+                format!("{}(:{})", literal, gen_type_spec(ty))
             } else {
                 format!(
                     "{}(:{}, {})",
-                    LITERAL,
+                    literal,
                     gen_type_spec(ty),
                     gen_validates(validates)
                 )
@@ -103,6 +481,12 @@ fn gen_macro(r#macro: &ir::Macro, nesting: usize) -> String {
 
             out
         }
+        ir::Macro::HashSchema { name, presence } => match presence {
+            ir::Presence::Value => format!(".hash({})", gen_def_name(name)),
+            ir::Presence::Filled => format!(".filled(:hash, {})", gen_def_name(name)),
+            ir::Presence::Maybe => format!(".maybe(:hash, {})", gen_def_name(name)),
+        },
+        ir::Macro::EachSchema { name } => format!(".each({})", gen_def_name(name)),
     }
 }
 
@@ -122,6 +506,111 @@ fn gen_validate(validate: &ir::Validate) -> String {
         ir::Validate::MinF(min) => format!("min: {min}"),
         ir::Validate::MaxSize(max) => format!("max_size: {max}"),
         ir::Validate::MinSize(min) => format!("min_size: {min}"),
+        ir::Validate::MaxDecimal(max) => format!("max: {}", gen_decimal(max)),
+        ir::Validate::MinDecimal(min) => format!("min: {}", gen_decimal(min)),
+        ir::Validate::LessThanDecimal(max) => format!("lt: {}", gen_decimal(max)),
+        ir::Validate::GreaterThanDecimal(min) => format!("gt: {}", gen_decimal(min)),
+        ir::Validate::IncludedIn(values) => format!(
+            "included_in: [{}]",
+            values
+                .iter()
+                .map(|value| gen_literal(value, 0))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn gen_decimal(literal: &str) -> String {
+    format!("BigDecimal(\"{literal}\")")
+}
+
+fn gen_literal(value: &serde_json::Value, nesting: usize) -> String {
+    match value {
+        serde_json::Value::Null => "nil".to_string(),
+        serde_json::Value::Bool(bool) => bool.to_string(),
+        serde_json::Value::Number(number) => number.to_string(),
+        serde_json::Value::String(string) => gen_string_literal(string),
+        serde_json::Value::Array(array) => format!(
+            "[{}].freeze",
+            array
+                .iter()
+                .map(|value| gen_literal(value, nesting))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        serde_json::Value::Object(object) => gen_hash_literal(object, nesting),
+    }
+}
+
+fn gen_defaults_literal(defaults: &[(String, ir::Default)], nesting: usize) -> String {
+    let entries = defaults
+        .iter()
+        .map(|(key, default)| {
+            let literal = match default {
+                ir::Default::Value(value) => gen_literal(value, nesting + 1),
+                ir::Default::Nested { defaults, default } => {
+                    let nested = gen_defaults_literal(defaults, nesting + 1);
+                    match default {
+                        Some(default) => format!(
+                            "Defaults::Nested.new({nested}, {})",
+                            gen_literal(default, nesting + 1)
+                        ),
+                        None => format!("Defaults::Nested.new({nested})"),
+                    }
+                }
+            };
+            format!(
+                "{}{} => {literal}",
+                indent(nesting + 1),
+                gen_string_literal(key)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    format!("{{\n{entries}\n{}}}.freeze", indent(nesting))
+}
+
+fn gen_hash_literal(object: &serde_json::Map<String, serde_json::Value>, nesting: usize) -> String {
+    if object.is_empty() {
+        return "{}.freeze".to_string();
+    }
+
+    let entries = object
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "{}{} => {}",
+                indent(nesting + 1),
+                gen_string_literal(key),
+                gen_literal(value, nesting + 1)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    format!("{{\n{entries}\n{}}}.freeze", indent(nesting))
+}
+
+fn gen_string_literal(string: &str) -> String {
+    // JSON string escapes are also valid in a double-quoted Ruby string, except for interpolation.
+    serde_json::Value::String(string.to_string())
+        .to_string()
+        .replace('#', "\\#")
+}
+
+/// Quoted unless it is an identifier, e.g. `:"X-Request-ID"`.
+fn gen_symbol(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier {
+        format!(":{name}")
+    } else {
+        format!(":{}", gen_string_literal(name))
     }
 }
 
@@ -137,6 +626,7 @@ fn gen_def_name(name: &str) -> String {
 fn gen_schema_class(schema_class: &ir::SchemaClass) -> String {
     match schema_class {
         ir::SchemaClass::Params => "Dry::Schema::Params".to_string(),
+        ir::SchemaClass::Json => "Dry::Schema::JSON".to_string(),
     }
 }
 
@@ -144,6 +634,7 @@ fn gen_type_spec(ty: &ir::Type) -> String {
     match ty {
         ir::Type::Integer => "integer",
         ir::Type::Float => "float",
+        ir::Type::Decimal => "decimal",
         ir::Type::String => "string",
         ir::Type::Boolean => "boolean",
         ir::Type::Array => "array",
@@ -156,6 +647,7 @@ fn gen_type_predicate(ty: &ir::Type) -> String {
     match ty {
         ir::Type::Integer => "int?",
         ir::Type::Float => "float?",
+        ir::Type::Decimal => "decimal?",
         ir::Type::String => "str?",
         ir::Type::Boolean => "bool?",
         ir::Type::Array => "array?",
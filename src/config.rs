@@ -0,0 +1,140 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use crate::DocumentLoader;
+
+/// Options to customize the generated schemas.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub read_only: ReadOnlyPolicy,
+    pub deprecated: DeprecatedPolicy,
+    /// Expands `$ref`s to `components/schemas` in place instead of emitting named schemas.
+    pub inline_schemas: bool,
+    /// Bounds `format: int64` to its range, as `format: int32` always is.
+    /// Off by default, since the bounds are noise in most schemas.
+    pub int64_bounds: bool,
+    /// Loads the input file and the documents referred to by `$ref`.
+    /// `DefaultLoader` is used if it is not set.
+    pub loader: Option<Arc<dyn DocumentLoader>>,
+    /// How `DefaultLoader` fetches remote documents.
+    pub http: HttpConfig,
+    /// Caches remote documents on disk if it is set.
+    pub cache: Option<CacheConfig>,
+    /// Restricts which documents `$ref`s may load.
+    pub resolution: ResolutionPolicy,
+}
+
+/// Limits on the documents loaded for `$ref`s, against a malicious or careless spec.
+/// A document violating them is not loaded, and the violation is reported as a diagnostic.
+#[derive(Debug, Clone)]
+pub struct ResolutionPolicy {
+    /// Local documents must be inside the directory if it is set.
+    pub root_dir: Option<PathBuf>,
+    /// Schemes of the URLs which may be loaded, e.g. `file` and `https`.
+    pub allowed_schemes: Vec<String>,
+    /// Hosts of the remote URLs which may be loaded. Any host is allowed if it is not set.
+    pub allowed_hosts: Option<Vec<String>>,
+    /// Maximum size of a document in bytes.
+    pub max_document_size: Option<usize>,
+    /// Maximum number of documents loaded in addition to the root one.
+    pub max_documents: Option<usize>,
+}
+impl Default for ResolutionPolicy {
+    fn default() -> Self {
+        Self {
+            root_dir: None,
+            allowed_schemes: vec!["file".to_string(), "http".to_string(), "https".to_string()],
+            allowed_hosts: None,
+            max_document_size: None,
+            max_documents: None,
+        }
+    }
+}
+
+/// Options for fetching remote documents.
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub headers: Vec<HostHeader>,
+    pub connect_timeout: Duration,
+    /// Timeout of each request after connecting, including reading the response.
+    pub timeout: Duration,
+    /// How many times a request is retried after a network error or a server error(5xx, 429).
+    pub retries: u32,
+    /// Wait before the first retry, which doubles on each retry.
+    pub retry_backoff: Duration,
+}
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            headers: Vec::new(),
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(30),
+            retries: 2,
+            retry_backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Where remote documents are cached, and how the cache is used.
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Directory of the cached documents, which are named by the hash of the URL.
+    pub dir: PathBuf,
+    /// File recording the content hash of each remote document, e.g. next to the spec.
+    pub lockfile: PathBuf,
+    /// Resolves remote documents only from the cache, without the network,
+    /// and leaves the lockfile as it is.
+    pub offline: bool,
+    /// Fails if a remote document is not in the lockfile or its hash has changed,
+    /// and leaves the lockfile as it is.
+    pub frozen: bool,
+}
+
+/// A header sent with every request to the host, e.g. a token for a private schema repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostHeader {
+    /// `example.com`, or `example.com:8080` to match the port as well.
+    pub host: String,
+    pub name: String,
+    pub value: String,
+}
+impl std::str::FromStr for HostHeader {
+    type Err = anyhow::Error;
+
+    /// Parses `<host>=<name>:<value>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((host, header)) = s.split_once('=') else {
+            anyhow::bail!("Expected `<host>=<name>:<value>`.(header: {s})");
+        };
+        let Some((name, value)) = header.split_once(':') else {
+            anyhow::bail!("Expected `<host>=<name>:<value>`.(header: {s})");
+        };
+
+        Ok(Self {
+            host: host.trim().to_string(),
+            name: name.trim().to_string(),
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+/// How a `readOnly` property sent by a client is treated in request schemas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReadOnlyPolicy {
+    /// The property is left out of the schema, so it is dropped from the result.
+    #[default]
+    Ignore,
+    /// The property fails validation when it is present.
+    Reject,
+}
+
+/// How a `deprecated` parameter is treated in request schemas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DeprecatedPolicy {
+    /// The parameter is validated as usual, with a comment in the schema.
+    #[default]
+    Comment,
+    /// The parameter is validated as usual, and reported to `Deprecations.handler` when it is present.
+    Log,
+    /// The parameter fails validation when it is present.
+    Reject,
+}
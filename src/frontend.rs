@@ -0,0 +1,165 @@
+mod asyncapi;
+mod json_schema;
+mod number_literals;
+mod openapi31;
+mod swagger2;
+
+use openapiv3::OpenAPI;
+use serde_json::Value;
+
+use crate::{parse_json, parse_yaml, SupportFileType};
+
+pub(crate) use number_literals::NumberLiterals;
+
+/// The version of the input, which decides how it is converted into OpenAPI 3.0,
+/// the model the rest of the pipeline is built on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Dialect {
+    Swagger20,
+    OpenApi30,
+    OpenApi31,
+    /// A JSON Schema document, which has no operations.
+    JsonSchema,
+    /// AsyncAPI 2.x, whose messages are validated instead of operations.
+    AsyncApi,
+}
+impl Dialect {
+    fn of(document: &Value) -> Self {
+        if document.get("swagger").and_then(Value::as_str) == Some("2.0") {
+            return Dialect::Swagger20;
+        }
+        if let Some(version) = document.get("asyncapi").and_then(Value::as_str) {
+            if version.starts_with("2.") {
+                return Dialect::AsyncApi;
+            }
+        }
+        match document.get("openapi").and_then(Value::as_str) {
+            Some(version) if version.starts_with("3.1") => Dialect::OpenApi31,
+            _ => Dialect::OpenApi30,
+        }
+    }
+
+    /// Converts a document which the root document refers to.
+    /// Documents referred to from Swagger 2.0 are read as they are,
+    /// since their schemas are the same as the ones of OpenAPI 3.0.
+    pub(crate) fn convert_external(self, document: &mut Value, url: &str) -> Vec<String> {
+        match self {
+            Dialect::Swagger20 | Dialect::OpenApi30 => vec![],
+            Dialect::OpenApi31 | Dialect::JsonSchema | Dialect::AsyncApi => {
+                openapi31::convert_schemas(document, url)
+            }
+        }
+    }
+}
+
+/// The root document converted into OpenAPI 3.0.
+pub(crate) struct Document {
+    pub openapi: OpenAPI,
+    /// The converted document as JSON, which keeps what `OpenAPI` drops, such as `$defs`,
+    /// so that a reference can point to it.
+    pub raw: Value,
+    pub dialect: Dialect,
+    /// Messages of an AsyncAPI document.
+    pub messages: Vec<Message>,
+    /// Parts of the input which are changed or dropped by the conversion.
+    pub diagnostics: Vec<String>,
+    pub number_literals: NumberLiterals,
+}
+
+/// A message of an AsyncAPI document.
+pub(crate) struct Message {
+    pub name: String,
+    /// The reference of the payload schema.
+    pub payload: String,
+}
+
+pub(crate) fn parse(text: &str, file_type: SupportFileType) -> Document {
+    let mut document = parse_value(text, file_type);
+    let dialect = Dialect::of(&document);
+    let diagnostics = match dialect {
+        Dialect::OpenApi30 => {
+            // Deserialized from the text, so that an error points to the line.
+            let openapi = match file_type {
+                SupportFileType::Json => parse_json(text),
+                SupportFileType::Yaml => parse_yaml(text),
+            };
+            // The parsed value rather than the deserialized one, which drops what `OpenAPI` does not model.
+            return Document {
+                raw: document,
+                openapi,
+                dialect,
+                messages: vec![],
+                diagnostics: vec![],
+                number_literals: NumberLiterals::scan(text),
+            };
+        }
+        Dialect::Swagger20 => swagger2::convert(&mut document),
+        Dialect::OpenApi31 => openapi31::convert(&mut document),
+        Dialect::AsyncApi => {
+            let (openapi, messages, diagnostics) = asyncapi::convert(&mut document);
+            return Document {
+                openapi: deserialize(openapi),
+                raw: document,
+                dialect,
+                messages,
+                diagnostics,
+                number_literals: NumberLiterals::scan(text),
+            };
+        }
+        Dialect::JsonSchema => unreachable!(),
+    };
+
+    converted_document(document, dialect, diagnostics, text)
+}
+
+/// Reads a JSON Schema document as OpenAPI whose `components/schemas` are the root and its definitions.
+/// The root schema is named `title`, or `root_name` if it has no title.
+pub(crate) fn parse_json_schema(
+    text: &str,
+    file_type: SupportFileType,
+    root_name: &str,
+) -> Document {
+    let mut document = parse_value(text, file_type);
+    let root_name = document
+        .get("title")
+        .and_then(Value::as_str)
+        .unwrap_or(root_name)
+        .to_string();
+    let diagnostics = json_schema::convert(&mut document, &root_name);
+
+    converted_document(document, Dialect::JsonSchema, diagnostics, text)
+}
+
+fn parse_value(text: &str, file_type: SupportFileType) -> Value {
+    match file_type {
+        SupportFileType::Json => serde_json::from_str(text).ok(),
+        // Via YAML values, because YAML allows non-string keys such as status codes.
+        SupportFileType::Yaml => serde_yaml::from_str::<serde_yaml::Value>(text)
+            .ok()
+            .and_then(|document| serde_json::to_value(document).ok()),
+    }
+    .unwrap_or_default()
+}
+
+fn converted_document(
+    raw: Value,
+    dialect: Dialect,
+    diagnostics: Vec<String>,
+    text: &str,
+) -> Document {
+    Document {
+        openapi: deserialize(raw.clone()),
+        raw,
+        dialect,
+        messages: vec![],
+        diagnostics,
+        number_literals: NumberLiterals::scan(text),
+    }
+}
+
+fn deserialize(openapi: Value) -> OpenAPI {
+    match serde_json::from_value(openapi) {
+        Ok(openapi) => openapi,
+        Err(err) => panic!("Could not deserialize input\nerror: `{err}`\n"),
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+
+use serde_json::{json, Value};
+
+use super::{openapi31, Message};
+use openapi31::escape;
+
+/// `schemaFormat`s whose payloads are JSON Schema.
+const JSON_SCHEMA_FORMATS: [&str; 3] = [
+    "application/vnd.aai.asyncapi",
+    "application/schema+json",
+    "application/schema+yaml",
+];
+
+/// Finds the messages of the operations in `channels` of an AsyncAPI 2.x document,
+/// and returns OpenAPI 3.0 with `components/schemas` of the document, the messages and what is dropped.
+/// The document itself is left in place, so that a payload is referred to by a pointer into it.
+pub(super) fn convert(document: &mut Value) -> (Value, Vec<Message>, Vec<String>) {
+    // AsyncAPI 2.x schemas are a superset of JSON Schema draft-07.
+    let mut diagnostics = openapi31::convert_schemas(document, "");
+
+    let mut messages = vec![];
+    let mut names = HashSet::new();
+    let mut payloads = HashSet::new();
+    if let Some(channels) = document.get("channels").and_then(Value::as_object) {
+        for (channel, item) in channels {
+            for action in ["subscribe", "publish"] {
+                let Some(operation) = item.get(action) else {
+                    continue;
+                };
+                let pointer = format!("#/channels/{}/{action}/message", escape(channel));
+                let candidates = match operation.get("message") {
+                    Some(Value::Object(message)) => match message.get("oneOf") {
+                        Some(Value::Array(one_of)) => one_of
+                            .iter()
+                            .enumerate()
+                            .map(|(index, message)| (format!("{pointer}/oneOf/{index}"), message))
+                            .collect(),
+                        _ => vec![(pointer, &operation["message"])],
+                    },
+                    _ => vec![],
+                };
+
+                for (pointer, message) in candidates {
+                    let (reference, message) = match message.get("$ref").and_then(Value::as_str) {
+                        Some(reference) => (
+                            reference.to_string(),
+                            find_local(document, reference).unwrap_or(&Value::Null),
+                        ),
+                        None => (pointer, message),
+                    };
+                    if !message.is_null() && message.get("payload").is_none() {
+                        continue;
+                    }
+                    let payload = payload_reference(&reference);
+                    if !payloads.insert(payload.clone()) {
+                        continue;
+                    }
+
+                    let name = message_name(message, &reference, operation, channel, action);
+                    if let Some(format) = message.get("schemaFormat").and_then(Value::as_str) {
+                        if !JSON_SCHEMA_FORMATS
+                            .iter()
+                            .any(|json_format| format.starts_with(json_format))
+                        {
+                            diagnostics.push(format!(
+                                "`schemaFormat` is not supported. format: {format}, message: {name}"
+                            ));
+                            continue;
+                        }
+                    }
+                    let name = unique_name(&mut names, name);
+                    messages.push(Message { name, payload });
+                }
+            }
+        }
+    }
+
+    let openapi = json!({
+        "openapi": "3.0.3",
+        "info": document.get("info").cloned().unwrap_or_else(|| json!({})),
+        "paths": {},
+        "components": {
+            "schemas": document
+                .pointer("/components/schemas")
+                .cloned()
+                .unwrap_or_else(|| json!({})),
+        },
+    });
+
+    (openapi, messages, diagnostics)
+}
+
+/// `messageId`, `name`, the name in the reference, or the operation, in this order.
+fn message_name(
+    message: &Value,
+    reference: &str,
+    operation: &Value,
+    channel: &str,
+    action: &str,
+) -> String {
+    for key in ["messageId", "name"] {
+        if let Some(name) = message.get(key).and_then(Value::as_str) {
+            return name.to_string();
+        }
+    }
+    if !reference.starts_with("#/channels/") {
+        // The stem of the file if the whole file is the message, e.g. `user` of `messages/user.yaml`.
+        let name = match reference.split_once('#') {
+            Some((_, fragment)) => fragment.rsplit('/').next(),
+            None => reference
+                .rsplit('/')
+                .next()
+                .map(|file| file.split_once('.').map_or(file, |(stem, _)| stem)),
+        };
+        if let Some(name) = name.filter(|name| !name.is_empty()) {
+            return name.to_string();
+        }
+    }
+    if let Some(operation_id) = operation.get("operationId").and_then(Value::as_str) {
+        return operation_id.to_string();
+    }
+
+    format!("{channel} {action}")
+}
+
+/// The payload of the message which the reference points to,
+/// whose fragment is the root of the file if it has none, e.g. `messages/user.yaml#/payload`.
+fn payload_reference(reference: &str) -> String {
+    match reference.split_once('#') {
+        Some((file, fragment)) => format!("{file}#{}/payload", fragment.trim_end_matches('/')),
+        None => format!("{reference}#/payload"),
+    }
+}
+
+fn unique_name(names: &mut HashSet<String>, name: String) -> String {
+    let mut unique = name.clone();
+    let mut suffix = 1;
+    while !names.insert(unique.clone()) {
+        suffix += 1;
+        unique = format!("{name}{suffix}");
+    }
+
+    unique
+}
+
+/// The message in the document, if the reference is local.
+fn find_local<'a>(document: &'a Value, reference: &str) -> Option<&'a Value> {
+    document.pointer(reference.strip_prefix('#')?)
+}
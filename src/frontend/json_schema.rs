@@ -0,0 +1,84 @@
+use serde_json::{json, Map, Value};
+
+use super::openapi31;
+
+/// Keys of the schemas which are defined to be referred to, in draft-07 and 2020-12.
+const DEFINITIONS_KEYS: [&str; 2] = ["definitions", "$defs"];
+
+/// Converts a JSON Schema document into OpenAPI 3.0 without paths,
+/// whose `components/schemas` are the root schema named `root_name` and each of its definitions.
+/// Returns what is dropped.
+pub(super) fn convert(document: &mut Value, root_name: &str) -> Vec<String> {
+    // Before the schemas are moved, so that the diagnostics point into the input.
+    let diagnostics = openapi31::convert_schemas(document, "");
+    let mut root = match document.take() {
+        Value::Object(root) => root,
+        // `true` and `false` are also schemas, but they have nothing to validate.
+        _ => Map::new(),
+    };
+    let mut schemas = Map::new();
+    for key in DEFINITIONS_KEYS {
+        if let Some(Value::Object(definitions)) = root.remove(key) {
+            schemas.extend(definitions);
+        }
+    }
+    let mut root_name = root_name.to_string();
+    while schemas.contains_key(&root_name) {
+        root_name.push_str("Root");
+    }
+    root.remove("$schema");
+    root.remove("$id");
+
+    let mut components = Map::new();
+    components.insert(root_name.clone(), Value::Object(root));
+    components.extend(schemas);
+
+    let mut components = Value::Object(components);
+    rewrite_references(&mut components, &root_name);
+    *document = json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": root_name,
+            "version": "",
+        },
+        "paths": {},
+        "components": {
+            "schemas": components,
+        },
+    });
+
+    diagnostics
+}
+
+/// Rewrites the local references to where the schemas are moved.
+fn rewrite_references(value: &mut Value, root_name: &str) {
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::String(reference)) = object.get_mut("$ref") {
+                if let Some(pointer) = reference.strip_prefix('#') {
+                    *reference = rewrite_pointer(pointer, root_name);
+                }
+            }
+            for child in object.values_mut() {
+                rewrite_references(child, root_name);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                rewrite_references(value, root_name);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn rewrite_pointer(pointer: &str, root_name: &str) -> String {
+    for key in DEFINITIONS_KEYS {
+        if let Some(definition) = pointer.strip_prefix(&format!("/{key}/")) {
+            return format!("#/components/schemas/{definition}");
+        }
+    }
+
+    let root_name = root_name.replace('~', "~0").replace('/', "~1");
+    format!("#/components/schemas/{root_name}{pointer}")
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+/// Keywords whose values are emitted as decimal bounds.
+const BOUND_KEYWORDS: [&str; 4] = ["minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum"];
+
+/// Bounds of the source text which `f64` cannot represent exactly, keyed by their `f64` value,
+/// so that a decimal bound can be emitted as it is written, e.g. `99999999999999999.99`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NumberLiterals {
+    /// `None` if different literals round to the same `f64`, since it is unknown which one is meant.
+    literals: HashMap<u64, Option<String>>,
+}
+impl NumberLiterals {
+    /// Collects the literals of the bound keywords in a JSON or YAML text.
+    /// Other numbers, e.g. in examples or descriptions, are left out.
+    pub(crate) fn scan(text: &str) -> Self {
+        let mut number_literals = Self::default();
+        let bytes = text.as_bytes();
+        let mut start = 0;
+        while start < bytes.len() {
+            let end = literal_end(bytes, start);
+            if end == start {
+                start += 1;
+                continue;
+            }
+            if is_boundary(bytes, start.checked_sub(1))
+                && is_boundary(bytes, Some(end))
+                && is_bound_value(bytes, start)
+            {
+                number_literals.insert(&text[start..end]);
+            }
+            start = end;
+        }
+
+        number_literals
+    }
+
+    pub(crate) fn extend(&mut self, other: NumberLiterals) {
+        for literal in other.literals.into_values().flatten() {
+            self.insert(&literal);
+        }
+    }
+
+    /// The literal of the number as written, or its shortest round-trip form if it is not found.
+    /// Fails if different literals round to the number.
+    pub(crate) fn literal(&self, number: f64) -> Result<String> {
+        match self.literals.get(&number.to_bits()) {
+            Some(Some(literal)) => Ok(literal.clone()),
+            Some(None) => anyhow::bail!(
+                "Different bounds round to the same number, so it can not be written as it is.(number: {number})"
+            ),
+            None => Ok(number.to_string()),
+        }
+    }
+
+    fn insert(&mut self, literal: &str) {
+        let Ok(number) = literal.parse::<f64>() else {
+            return;
+        };
+        if !number.is_finite() || number.to_string() == literal {
+            return;
+        }
+        self.literals
+            .entry(number.to_bits())
+            .and_modify(|known| {
+                if known.as_deref() != Some(literal) {
+                    *known = None;
+                }
+            })
+            .or_insert_with(|| Some(literal.to_string()));
+    }
+}
+
+/// The end of `-?digits(.digits)?([eE][+-]?digits)?` from `start`, or `start` if there is none.
+fn literal_end(bytes: &[u8], start: usize) -> usize {
+    let mut end = start;
+    if bytes.get(end) == Some(&b'-') {
+        end += 1;
+    }
+    let Some(integer_end) = digits_end(bytes, end) else {
+        return start;
+    };
+    end = integer_end;
+    if bytes.get(end) == Some(&b'.') {
+        if let Some(fraction_end) = digits_end(bytes, end + 1) {
+            end = fraction_end;
+        }
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let mut exponent = end + 1;
+        if matches!(bytes.get(exponent), Some(b'+' | b'-')) {
+            exponent += 1;
+        }
+        if let Some(exponent_end) = digits_end(bytes, exponent) {
+            end = exponent_end;
+        }
+    }
+
+    end
+}
+
+fn digits_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let count = bytes[start.min(bytes.len())..]
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    (count > 0).then_some(start + count)
+}
+
+/// Whether the literal at `start` is the value of a bound keyword,
+/// e.g. `"maximum": 1.5` in JSON or `maximum: 1.5` in YAML.
+fn is_bound_value(bytes: &[u8], start: usize) -> bool {
+    let before = &bytes[..start];
+    let Some(before) = before.trim_ascii_end().strip_suffix(b":") else {
+        return false;
+    };
+    let before = before.trim_ascii_end();
+    let before = before
+        .strip_suffix(b"\"")
+        .or_else(|| before.strip_suffix(b"'"))
+        .unwrap_or(before);
+    BOUND_KEYWORDS.iter().any(|keyword| {
+        before.strip_suffix(keyword.as_bytes()).is_some_and(|rest| {
+            !rest
+                .last()
+                .is_some_and(|byte| byte.is_ascii_alphanumeric() || *byte == b'_')
+        })
+    })
+}
+
+/// Whether the literal is not a part of a word, such as `v1.2`.
+fn is_boundary(bytes: &[u8], index: Option<usize>) -> bool {
+    match index.and_then(|index| bytes.get(index)) {
+        Some(byte) => !(byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'.' | b'-')),
+        None => true,
+    }
+}
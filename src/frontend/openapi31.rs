@@ -0,0 +1,224 @@
+use serde_json::{json, Map, Value};
+
+/// Keys of maps whose keys are names chosen by the author, not keywords.
+const NAMED_MAP_KEYS: [&str; 20] = [
+    "schemas",
+    "properties",
+    "patternProperties",
+    "dependentSchemas",
+    "$defs",
+    // Draft-07 and earlier.
+    "definitions",
+    // AsyncAPI.
+    "channels",
+    "messages",
+    "responses",
+    "parameters",
+    "requestBodies",
+    "headers",
+    "examples",
+    "links",
+    "callbacks",
+    "content",
+    "encoding",
+    "securitySchemes",
+    "pathItems",
+    "webhooks",
+];
+
+/// Keys of values which are not OpenAPI objects.
+const DATA_KEYS: [&str; 5] = ["example", "default", "enum", "const", "value"];
+
+/// Converts the root document of OpenAPI 3.1, whose schemas are JSON Schema 2020-12,
+/// and returns what is dropped.
+/// Keywords are rewritten into the OpenAPI 3.0 ones that mean the same,
+/// e.g. `type: [string, "null"]` into `type: string` and `nullable: true`.
+pub(super) fn convert(document: &mut Value) -> Vec<String> {
+    let mut diagnostics = vec![];
+    let Value::Object(object) = document else {
+        return diagnostics;
+    };
+    object.insert("openapi".to_string(), json!("3.0.3"));
+    // `paths` is optional in 3.1.
+    object.entry("paths").or_insert_with(|| json!({}));
+
+    visit(document, "#", &mut diagnostics);
+
+    diagnostics
+}
+
+/// Converts the schemas in a document which the root document refers to.
+pub(super) fn convert_schemas(document: &mut Value, url: &str) -> Vec<String> {
+    let mut diagnostics = vec![];
+    visit(document, &format!("{url}#"), &mut diagnostics);
+
+    diagnostics
+}
+
+fn visit(value: &mut Value, pointer: &str, diagnostics: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, child) in object.iter_mut() {
+                let child_pointer = format!("{pointer}/{}", escape(key));
+                if NAMED_MAP_KEYS.contains(&key.as_str()) {
+                    if let Value::Object(named) = child {
+                        for (name, child) in named.iter_mut() {
+                            visit(
+                                child,
+                                &format!("{child_pointer}/{}", escape(name)),
+                                diagnostics,
+                            );
+                        }
+                        continue;
+                    }
+                }
+                // `examples` of a schema is an array of values.
+                if DATA_KEYS.contains(&key.as_str()) || key == "examples" || key.starts_with("x-") {
+                    continue;
+                }
+                visit(child, &child_pointer, diagnostics);
+            }
+            // After the children, so that `prefixItems` are compared in the converted form.
+            convert_keywords(object, pointer, diagnostics);
+        }
+        Value::Array(values) => {
+            for (index, child) in values.iter_mut().enumerate() {
+                visit(child, &format!("{pointer}/{index}"), diagnostics);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn convert_keywords(object: &mut Map<String, Value>, pointer: &str, diagnostics: &mut Vec<String>) {
+    convert_type(object);
+    if let Some(value) = object.remove("const") {
+        object.entry("enum").or_insert_with(|| json!([value]));
+    }
+    // Only the first example is kept, since 3.0 allows one.
+    if let Some(Value::Array(examples)) = object.get("examples") {
+        let example = examples.first().cloned();
+        object.remove("examples");
+        if let Some(example) = example {
+            object.entry("example").or_insert(example);
+        }
+    }
+    convert_exclusive_bound(object, "exclusiveMinimum", "minimum", |bound, exclusive| {
+        bound <= exclusive
+    });
+    convert_exclusive_bound(object, "exclusiveMaximum", "maximum", |bound, exclusive| {
+        bound >= exclusive
+    });
+    if let Some(items) = convert_tuple_items(object) {
+        convert_prefix_items(object, "items", &items, pointer, diagnostics);
+    } else if let Some(Value::Array(prefix_items)) = object.remove("prefixItems") {
+        convert_prefix_items(object, "prefixItems", &prefix_items, pointer, diagnostics);
+    }
+}
+
+/// `type: [T, "null"]` into `type: T` and `nullable: true`.
+fn convert_type(object: &mut Map<String, Value>) {
+    let types = match object.get("type") {
+        Some(Value::Array(types)) => types.clone(),
+        Some(Value::String(ty)) if ty == "null" => vec![json!("null")],
+        _ => return,
+    };
+    object.remove("type");
+
+    let non_null_types = types
+        .iter()
+        .filter(|ty| ty.as_str() != Some("null"))
+        .cloned()
+        .collect::<Vec<_>>();
+    if non_null_types.len() < types.len() {
+        object.insert("nullable".to_string(), json!(true));
+    }
+    match non_null_types.as_slice() {
+        [] => (),
+        [ty] => {
+            object.insert("type".to_string(), ty.clone());
+        }
+        // Each type is a separate schema in 3.0.
+        types => {
+            let any_of = types.iter().map(|ty| json!({ "type": ty })).collect();
+            object.insert("anyOf".to_string(), Value::Array(any_of));
+        }
+    }
+}
+
+/// `exclusiveMinimum: 1` into `minimum: 1` and `exclusiveMinimum: true`,
+/// unless `minimum` is stricter than it.
+fn convert_exclusive_bound(
+    object: &mut Map<String, Value>,
+    exclusive_key: &str,
+    key: &str,
+    is_looser: impl Fn(f64, f64) -> bool,
+) {
+    let Some(Value::Number(exclusive)) = object.get(exclusive_key).cloned() else {
+        return;
+    };
+    object.remove(exclusive_key);
+
+    let bound = object.get(key).and_then(Value::as_f64);
+    if bound.is_none_or(|bound| is_looser(bound, exclusive.as_f64().unwrap())) {
+        object.insert(key.to_string(), Value::Number(exclusive));
+        object.insert(exclusive_key.to_string(), json!(true));
+    }
+}
+
+/// Draft-07 tuples, `items: [...]` followed by `additionalItems`,
+/// into the `items` after the prefix as in 2020-12, and returns the prefix.
+fn convert_tuple_items(object: &mut Map<String, Value>) -> Option<Vec<Value>> {
+    let additional_items = object.remove("additionalItems");
+    let Some(Value::Array(prefix_items)) = object.get_mut("items") else {
+        return None;
+    };
+    let prefix_items = std::mem::take(prefix_items);
+    match additional_items {
+        Some(additional_items) => object.insert("items".to_string(), additional_items),
+        None => object.remove("items"),
+    };
+
+    Some(prefix_items)
+}
+
+/// `prefixItems` has no counterpart in 3.0, so it is only kept
+/// when every item is validated with the same schema.
+fn convert_prefix_items(
+    object: &mut Map<String, Value>,
+    keyword: &str,
+    prefix_items: &[Value],
+    pointer: &str,
+    diagnostics: &mut Vec<String>,
+) {
+    let items = object.remove("items");
+    if items == Some(json!(false)) {
+        let max_items = object
+            .get("maxItems")
+            .and_then(Value::as_u64)
+            .map_or(prefix_items.len() as u64, |max| {
+                max.min(prefix_items.len() as u64)
+            });
+        object.insert("maxItems".to_string(), json!(max_items));
+    }
+
+    let rest = match &items {
+        // Items after the prefix are anything.
+        None | Some(Value::Bool(true)) => None,
+        Some(Value::Bool(false)) => prefix_items.first(),
+        Some(items) => Some(items),
+    };
+    match rest {
+        Some(rest) if prefix_items.iter().all(|item| item == rest) => {
+            object.insert("items".to_string(), rest.clone());
+        }
+        _ => diagnostics.push(format!(
+            "`{keyword}` is validated only as an array. pointer: {pointer}"
+        )),
+    }
+}
+
+/// Escapes a key as a JSON Pointer token(RFC 6901).
+pub(super) fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,523 @@
+use serde_json::{json, Map, Value};
+
+/// Media type of bodies when neither the operation nor the document declares `consumes` or `produces`.
+const DEFAULT_MEDIA_TYPE: &str = "application/json";
+
+/// Keywords of a non-body parameter which describe its value, and go to `schema` in 3.0.
+const PARAMETER_SCHEMA_KEYS: [&str; 17] = [
+    "type",
+    "format",
+    "items",
+    "default",
+    "maximum",
+    "exclusiveMaximum",
+    "minimum",
+    "exclusiveMinimum",
+    "maxLength",
+    "minLength",
+    "pattern",
+    "maxItems",
+    "minItems",
+    "uniqueItems",
+    "enum",
+    "multipleOf",
+    "x-nullable",
+];
+
+/// Converts the root document of Swagger 2.0 into OpenAPI 3.0, and returns what is dropped.
+/// `definitions`, `parameters` and `responses` move to `components`,
+/// and body and form parameters become request bodies.
+pub(super) fn convert(document: &mut Value) -> Vec<String> {
+    let mut converter = Converter {
+        global_parameters: document
+            .get("parameters")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default(),
+        diagnostics: vec![],
+    };
+    let Value::Object(swagger) = document else {
+        return converter.diagnostics;
+    };
+    let consumes = media_types(swagger.get("consumes"));
+    let produces = media_types(swagger.get("produces"));
+
+    let mut openapi = Map::new();
+    openapi.insert("openapi".to_string(), json!("3.0.3"));
+    if let Some(info) = swagger.remove("info") {
+        openapi.insert("info".to_string(), info);
+    }
+    if let Some(servers) = servers(swagger) {
+        openapi.insert("servers".to_string(), servers);
+    }
+
+    let mut paths = swagger
+        .remove("paths")
+        .and_then(|paths| match paths {
+            Value::Object(paths) => Some(paths),
+            _ => None,
+        })
+        .unwrap_or_default();
+    for (path, item) in paths.iter_mut() {
+        if let Value::Object(item) = item {
+            converter.convert_path_item(path, item, &consumes, &produces);
+        }
+    }
+    openapi.insert("paths".to_string(), Value::Object(paths));
+
+    let mut components = Map::new();
+    if let Some(definitions) = swagger.remove("definitions") {
+        components.insert("schemas".to_string(), definitions);
+    }
+    let mut parameters = Map::new();
+    let mut request_bodies = Map::new();
+    for (name, parameter) in converter.global_parameters.clone() {
+        let Value::Object(mut parameter) = parameter else {
+            continue;
+        };
+        match parameter.get("in").and_then(Value::as_str) {
+            Some("body") => {
+                request_bodies.insert(name, body_request_body(&mut parameter, &consumes));
+            }
+            Some("formData") => converter.diagnostics.push(format!(
+                "`formData` parameter in `parameters` is not supported. name: {name}"
+            )),
+            _ => {
+                let parameter = converter.convert_parameter(parameter, &name);
+                parameters.insert(name, parameter);
+            }
+        }
+    }
+    if !parameters.is_empty() {
+        components.insert("parameters".to_string(), Value::Object(parameters));
+    }
+    if !request_bodies.is_empty() {
+        components.insert("requestBodies".to_string(), Value::Object(request_bodies));
+    }
+    if let Some(Value::Object(responses)) = swagger.remove("responses") {
+        let responses = responses
+            .into_iter()
+            .map(|(name, response)| (name, convert_response(response, &produces)))
+            .collect();
+        components.insert("responses".to_string(), Value::Object(responses));
+    }
+    if let Some(Value::Object(security_definitions)) = swagger.remove("securityDefinitions") {
+        let security_schemes = security_definitions
+            .into_iter()
+            .map(|(name, scheme)| (name, convert_security_scheme(scheme)))
+            .collect();
+        components.insert(
+            "securitySchemes".to_string(),
+            Value::Object(security_schemes),
+        );
+    }
+    if !components.is_empty() {
+        openapi.insert("components".to_string(), Value::Object(components));
+    }
+    for key in ["security", "tags", "externalDocs"] {
+        if let Some(value) = swagger.remove(key) {
+            openapi.insert(key.to_string(), value);
+        }
+    }
+    for (key, value) in swagger.iter() {
+        if key.starts_with("x-") {
+            openapi.insert(key.clone(), value.clone());
+        }
+    }
+
+    let mut openapi = Value::Object(openapi);
+    convert_schemas(&mut openapi);
+    *document = openapi;
+
+    converter.diagnostics
+}
+
+struct Converter {
+    /// `parameters` of the document, to tell whether a referenced parameter is a body.
+    global_parameters: Map<String, Value>,
+    diagnostics: Vec<String>,
+}
+impl Converter {
+    fn convert_path_item(
+        &mut self,
+        path: &str,
+        item: &mut Map<String, Value>,
+        consumes: &[String],
+        produces: &[String],
+    ) {
+        let shared_parameters = item.remove("parameters");
+        if let Some(Value::Array(parameters)) = &shared_parameters {
+            let (parameters, body) = self.convert_parameters(parameters.clone(), path, consumes);
+            if body.is_some() {
+                self.diagnostics.push(format!(
+                    "Body parameter shared by the operations of a path is not supported. path: {path}"
+                ));
+            }
+            item.insert("parameters".to_string(), Value::Array(parameters));
+        }
+
+        for method in ["get", "put", "post", "delete", "options", "head", "patch"] {
+            let Some(Value::Object(operation)) = item.get_mut(method) else {
+                continue;
+            };
+            let consumes = operation
+                .get("consumes")
+                .map(|consumes| media_types(Some(consumes)))
+                .unwrap_or_else(|| consumes.to_vec());
+            let produces = operation
+                .get("produces")
+                .map(|produces| media_types(Some(produces)))
+                .unwrap_or_else(|| produces.to_vec());
+            operation.remove("consumes");
+            operation.remove("produces");
+            operation.remove("schemes");
+
+            let ctx = format!("{method} {path}");
+            if let Some(Value::Array(parameters)) = operation.remove("parameters") {
+                let (parameters, request_body) =
+                    self.convert_parameters(parameters, &ctx, &consumes);
+                if !parameters.is_empty() {
+                    operation.insert("parameters".to_string(), Value::Array(parameters));
+                }
+                if let Some(request_body) = request_body {
+                    operation.insert("requestBody".to_string(), request_body);
+                }
+            }
+            if let Some(Value::Object(responses)) = operation.get_mut("responses") {
+                for response in responses.values_mut() {
+                    *response = convert_response(response.take(), &produces);
+                }
+            }
+        }
+    }
+
+    /// Splits the parameters into the ones of 3.0 and a request body.
+    fn convert_parameters(
+        &mut self,
+        parameters: Vec<Value>,
+        ctx: &str,
+        consumes: &[String],
+    ) -> (Vec<Value>, Option<Value>) {
+        let mut converted = vec![];
+        let mut request_body = None;
+        let mut form_parameters = vec![];
+        for parameter in parameters {
+            let Value::Object(mut parameter) = parameter else {
+                continue;
+            };
+            if let Some(Value::String(reference)) = parameter.get("$ref") {
+                // Expanded, since the media types are the ones of the operation.
+                match self.global_body_parameter(reference) {
+                    Some(mut body) => request_body = Some(body_request_body(&mut body, consumes)),
+                    None => converted.push(Value::Object(parameter)),
+                }
+                continue;
+            }
+            match parameter.get("in").and_then(Value::as_str) {
+                Some("body") => request_body = Some(body_request_body(&mut parameter, consumes)),
+                Some("formData") => form_parameters.push(parameter),
+                _ => converted.push(self.convert_parameter(parameter, ctx)),
+            }
+        }
+        if !form_parameters.is_empty() {
+            request_body = Some(form_request_body(form_parameters, consumes));
+        }
+
+        (converted, request_body)
+    }
+
+    /// The body parameter in `parameters` of the document which the reference points to.
+    fn global_body_parameter(&self, reference: &str) -> Option<Map<String, Value>> {
+        let name = reference.strip_prefix("#/parameters/")?;
+        match self.global_parameters.get(name) {
+            Some(Value::Object(parameter))
+                if parameter.get("in").and_then(Value::as_str) == Some("body") =>
+            {
+                Some(parameter.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Moves the keywords of the value into `schema`,
+    /// and `collectionFormat` into `style` and `explode`.
+    fn convert_parameter(&mut self, mut parameter: Map<String, Value>, ctx: &str) -> Value {
+        let mut schema = Map::new();
+        for key in PARAMETER_SCHEMA_KEYS {
+            if let Some(value) = parameter.remove(key) {
+                schema.insert(key.to_string(), value);
+            }
+        }
+        // Formats of the items are the same as the ones of the parameter, but nested arrays are not split in 3.0.
+        if let Some(Value::Object(items)) = schema.get_mut("items") {
+            items.remove("collectionFormat");
+        }
+        // `csv` is the default of arrays in 2.0, while exploded `form` is the one of queries in 3.0.
+        let collection_format = parameter.remove("collectionFormat").or_else(|| {
+            (schema.get("type").and_then(Value::as_str) == Some("array")).then(|| json!("csv"))
+        });
+        if let Some(collection_format) = collection_format {
+            let location = parameter
+                .get("in")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            match (collection_format.as_str(), location) {
+                (Some("csv"), "query" | "cookie") => {
+                    parameter.insert("style".to_string(), json!("form"));
+                    parameter.insert("explode".to_string(), json!(false));
+                }
+                (Some("csv"), _) => {
+                    parameter.insert("style".to_string(), json!("simple"));
+                }
+                (Some("ssv"), _) => {
+                    parameter.insert("style".to_string(), json!("spaceDelimited"));
+                }
+                (Some("pipes"), _) => {
+                    parameter.insert("style".to_string(), json!("pipeDelimited"));
+                }
+                (Some("multi"), _) => {
+                    parameter.insert("style".to_string(), json!("form"));
+                    parameter.insert("explode".to_string(), json!(true));
+                }
+                (format, _) => self.diagnostics.push(format!(
+                    "`collectionFormat` is not supported. format: {}, parameter: {}, in {ctx}",
+                    format.unwrap_or_default(),
+                    parameter
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                )),
+            }
+        }
+        parameter.insert("schema".to_string(), Value::Object(schema));
+
+        Value::Object(parameter)
+    }
+}
+
+fn media_types(media_types: Option<&Value>) -> Vec<String> {
+    strings_or(media_types, DEFAULT_MEDIA_TYPE)
+}
+
+/// `content` with the schema for each media type.
+fn content(schema: Value, media_types: &[String]) -> Value {
+    let content = media_types
+        .iter()
+        .map(|media_type| (media_type.clone(), json!({ "schema": schema })))
+        .collect();
+
+    Value::Object(content)
+}
+
+/// `in: body` into a request body of the media types in `consumes`.
+fn body_request_body(parameter: &mut Map<String, Value>, consumes: &[String]) -> Value {
+    let schema = parameter.remove("schema").unwrap_or_else(|| json!({}));
+    let mut request_body = Map::new();
+    if let Some(description) = parameter.remove("description") {
+        request_body.insert("description".to_string(), description);
+    }
+    request_body.insert("content".to_string(), content(schema, consumes));
+    if let Some(required) = parameter.remove("required") {
+        request_body.insert("required".to_string(), required);
+    }
+
+    Value::Object(request_body)
+}
+
+/// `in: formData` into a request body of an object whose properties are the parameters.
+fn form_request_body(parameters: Vec<Map<String, Value>>, consumes: &[String]) -> Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+    for mut parameter in parameters {
+        let name = parameter
+            .remove("name")
+            .and_then(|name| name.as_str().map(str::to_string))
+            .unwrap_or_default();
+        if parameter.remove("required") == Some(json!(true)) {
+            required.push(json!(name));
+        }
+        let mut property = Map::new();
+        for key in PARAMETER_SCHEMA_KEYS.iter().chain(&["description"]) {
+            if let Some(value) = parameter.remove(*key) {
+                property.insert(key.to_string(), value);
+            }
+        }
+        properties.insert(name, Value::Object(property));
+    }
+    let mut schema = json!({
+        "type": "object",
+        "properties": properties,
+    });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+
+    // Only the form media types can carry form parameters.
+    let form_media_types = consumes
+        .iter()
+        .filter(|media_type| {
+            media_type.starts_with("application/x-www-form-urlencoded")
+                || media_type.starts_with("multipart/form-data")
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let media_types = if form_media_types.is_empty() {
+        vec!["application/x-www-form-urlencoded".to_string()]
+    } else {
+        form_media_types
+    };
+
+    json!({ "content": content(schema, &media_types) })
+}
+
+/// `schema` of a response into `content` of the media types in `produces`.
+fn convert_response(response: Value, produces: &[String]) -> Value {
+    let Value::Object(mut response) = response else {
+        return response;
+    };
+    if response.contains_key("$ref") {
+        return Value::Object(response);
+    }
+    if let Some(schema) = response.remove("schema") {
+        response.insert("content".to_string(), content(schema, produces));
+    }
+    if let Some(Value::Object(headers)) = response.get_mut("headers") {
+        for header in headers.values_mut() {
+            let Value::Object(header) = header else {
+                continue;
+            };
+            let mut schema = Map::new();
+            for key in PARAMETER_SCHEMA_KEYS {
+                if let Some(value) = header.remove(key) {
+                    schema.insert(key.to_string(), value);
+                }
+            }
+            header.remove("collectionFormat");
+            header.insert("schema".to_string(), Value::Object(schema));
+        }
+    }
+    // Examples are keyed by media type in 2.0, and by name in 3.0.
+    response.remove("examples");
+
+    Value::Object(response)
+}
+
+fn convert_security_scheme(scheme: Value) -> Value {
+    let Value::Object(mut scheme) = scheme else {
+        return scheme;
+    };
+    match scheme.get("type").and_then(Value::as_str) {
+        Some("basic") => {
+            scheme.insert("type".to_string(), json!("http"));
+            scheme.insert("scheme".to_string(), json!("basic"));
+        }
+        Some("oauth2") => {
+            let flow = scheme.remove("flow");
+            let mut converted_flow = Map::new();
+            for key in ["authorizationUrl", "tokenUrl", "scopes"] {
+                if let Some(value) = scheme.remove(key) {
+                    converted_flow.insert(key.to_string(), value);
+                }
+            }
+            let flow = match flow.as_ref().and_then(Value::as_str) {
+                Some("application") => "clientCredentials",
+                Some("accessCode") => "authorizationCode",
+                Some(flow) => flow,
+                None => "implicit",
+            };
+            scheme.insert(
+                "flows".to_string(),
+                json!({ flow: Value::Object(converted_flow) }),
+            );
+        }
+        _ => (),
+    }
+
+    Value::Object(scheme)
+}
+
+/// `host`, `basePath` and `schemes` into `servers`.
+fn servers(swagger: &mut Map<String, Value>) -> Option<Value> {
+    let host = swagger.remove("host");
+    let base_path = swagger.remove("basePath");
+    let schemes = swagger.remove("schemes");
+    if host.is_none() && base_path.is_none() {
+        return None;
+    }
+
+    let host = host.as_ref().and_then(Value::as_str).unwrap_or_default();
+    let base_path = base_path
+        .as_ref()
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let schemes = strings_or(schemes.as_ref(), "https");
+    let servers = schemes
+        .iter()
+        .map(|scheme| {
+            let url = if host.is_empty() {
+                base_path.to_string()
+            } else {
+                format!("{scheme}://{host}{base_path}")
+            };
+            json!({ "url": url })
+        })
+        .collect();
+
+    Some(Value::Array(servers))
+}
+
+/// The strings in the array, or the default if there are none.
+fn strings_or(values: Option<&Value>, default: &str) -> Vec<String> {
+    let values = values
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str().map(str::to_string))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    if values.is_empty() {
+        vec![default.to_string()]
+    } else {
+        values
+    }
+}
+
+/// Rewrites the local references to where the objects are moved,
+/// and the schema keywords of 2.0 into the ones of 3.0.
+fn convert_schemas(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::String(reference)) = object.get_mut("$ref") {
+                for (from, to) in [
+                    ("#/definitions/", "#/components/schemas/"),
+                    ("#/parameters/", "#/components/parameters/"),
+                    ("#/responses/", "#/components/responses/"),
+                ] {
+                    if let Some(name) = reference.strip_prefix(from) {
+                        *reference = format!("{to}{name}");
+                        break;
+                    }
+                }
+            }
+            if object.remove("x-nullable") == Some(json!(true)) {
+                object.insert("nullable".to_string(), json!(true));
+            }
+            if object.get("type").and_then(Value::as_str) == Some("file") {
+                object.insert("type".to_string(), json!("string"));
+                object.insert("format".to_string(), json!("binary"));
+            }
+            for child in object.values_mut() {
+                convert_schemas(child);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                convert_schemas(value);
+            }
+        }
+        _ => (),
+    }
+}
@@ -1,9 +1,11 @@
 pub mod ir;
 
-use crate::ast_builder::ast;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
-pub fn build(root_schema: &ast::RootSchema) -> IrResult {
-    let builder = IrBuilder::new();
+use crate::{ast_builder::ast, Config, DeprecatedPolicy, ReadOnlyPolicy};
+
+pub fn build(root_schema: &ast::RootSchema, config: &Config) -> IrResult {
+    let builder = IrBuilder::new(config, root_schema);
     builder.build(root_schema)
 }
 
@@ -11,36 +13,299 @@ pub struct IrResult {
     pub ir: ir::Defs,
 }
 
-struct IrBuilder {}
-impl IrBuilder {
-    fn new() -> Self {
-        IrBuilder {}
+/// Whether the definition validates what a client sends or what a server returns.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Request,
+    Response,
+    /// Named schemas are shared by requests and responses, so no property is left out.
+    Shared,
+}
+
+struct IrBuilder<'a> {
+    config: &'a Config,
+    /// Coercion helpers used by any definition.
+    coercers: BTreeSet<ir::Coercer>,
+    /// Rule helpers used by any definition.
+    checkers: BTreeSet<ir::Checker>,
+    /// Rules of the definition currently being built.
+    def_rules: Vec<ir::Rule>,
+    /// Path from the definition currently being built to the value being built.
+    path: Vec<ir::PathSegment>,
+    /// Names of the definitions with rules, which can not be nested as a schema.
+    contracts: HashSet<String>,
+    /// Whether any definition reports deprecated keys.
+    deprecations: bool,
+    /// Direction of the definition currently being built.
+    direction: Direction,
+    /// Class of the definition currently being built, which the named schemas it refers to share.
+    class: ir::SchemaClass,
+    /// Named schemas with `readOnly` properties, directly or through other named schemas.
+    read_only_schemas: HashSet<String>,
+    /// Named schemas with `writeOnly` properties, directly or through other named schemas.
+    write_only_schemas: HashSet<String>,
+    /// Variants of the named schemas which any definition refers to.
+    variants: HashSet<(String, Direction, ir::SchemaClass)>,
+    /// Types of the named schemas keyed by name.
+    named_schemas: HashMap<&'a str, &'a ast::Type>,
+}
+impl<'a> IrBuilder<'a> {
+    fn new(config: &'a Config, ast: &'a ast::RootSchema) -> Self {
+        IrBuilder {
+            config,
+            coercers: BTreeSet::new(),
+            checkers: BTreeSet::new(),
+            def_rules: vec![],
+            path: vec![],
+            contracts: HashSet::new(),
+            deprecations: false,
+            direction: Direction::Request,
+            class: ir::SchemaClass::Json,
+            read_only_schemas: HashSet::new(),
+            write_only_schemas: HashSet::new(),
+            variants: HashSet::new(),
+            named_schemas: ast
+                .named_schemas
+                .iter()
+                .map(|named_schema| (named_schema.name.as_str(), &named_schema.ty))
+                .collect(),
+        }
     }
 
-    fn build(&self, ast: &ast::RootSchema) -> IrResult {
+    fn build(mut self, ast: &ast::RootSchema) -> IrResult {
         let mut defs = vec![];
 
+        self.collect_variants(ast);
+        for named_schema in &ast.named_schemas {
+            for direction in [Direction::Shared, Direction::Request, Direction::Response] {
+                for class in [ir::SchemaClass::Json, ir::SchemaClass::Params] {
+                    let variant = (named_schema.name.clone(), direction, class);
+                    if self.variants.contains(&variant) {
+                        self.direction = direction;
+                        self.class = class;
+                        defs.push(self.build_named_schema(named_schema));
+                    }
+                }
+            }
+        }
+
+        // A message is validated as it is, whichever side it is sent from.
+        self.direction = Direction::Shared;
+        self.class = ir::SchemaClass::Json;
+        for message in &ast.messages {
+            let stmts = self.build_properties(&message.body.properties).stmts;
+            let defaults = self.build_properties_defaults(&message.body.properties);
+            defs.push(self.build_def(message.name.clone(), stmts, defaults));
+        }
+
         for path_item in &ast.path_items {
             for operation in &path_item.operations {
-                let mut stmts = vec![];
-                for param in &operation.queries {
-                    stmts.push(self.build_property(param.name.clone(), param.required, &param.ty));
+                let id = operation.id.clone().unwrap();
+
+                self.direction = Direction::Request;
+                let def = self.build_params_def(id.clone(), &operation.queries);
+                defs.push(def);
+                if !operation.path_params.is_empty() {
+                    let def = self
+                        .build_params_def(format!("{id} path parameters"), &operation.path_params);
+                    defs.push(def);
+                }
+                if !operation.headers.is_empty() {
+                    let def = self.build_params_def(format!("{id} headers"), &operation.headers);
+                    defs.push(def);
+                }
+
+                if let Some(request_body) = &operation.request_body {
+                    self.class = body_class(request_body);
+                    let stmts = self.build_properties(&request_body.properties).stmts;
+                    let defaults = self.build_properties_defaults(&request_body.properties);
+                    defs.push(self.build_def(format!("{id} request body"), stmts, defaults));
                 }
 
-                defs.push(ir::Def {
-                    name: operation.id.clone().unwrap(),
-                    class: ir::SchemaClass::Params,
-                    block: ir::Block::new(stmts),
-                });
+                self.direction = Direction::Response;
+                for response in &operation.responses {
+                    if !response.headers.is_empty() {
+                        let name = format!("{id} response {} headers", response.status);
+                        let def = self.build_params_def(name, &response.headers);
+                        defs.push(def);
+                    }
+                    if let Some(body) = &response.body {
+                        self.class = body_class(body);
+                        let stmts = self.build_properties(&body.properties).stmts;
+                        defs.push(self.build_def(
+                            format!("{id} response {}", response.status),
+                            stmts,
+                            vec![],
+                        ));
+                    }
+                }
             }
         }
 
         IrResult {
-            ir: ir::Defs { defs },
+            ir: ir::Defs {
+                coercers: self.coercers.into_iter().collect(),
+                checkers: self.checkers.into_iter().collect(),
+                deprecations: self.deprecations,
+                defs,
+            },
+        }
+    }
+
+    fn build_named_schema(&mut self, named_schema: &ast::NamedSchema) -> ir::Def {
+        let stmts = match &named_schema.ty {
+            ast::Type::Object { properties, .. } => self.build_properties(properties).stmts,
+            _ => vec![],
+        };
+
+        let name = self.named_schema_def_name(&named_schema.name);
+        self.build_def(name, stmts, vec![])
+    }
+
+    fn build_def(
+        &mut self,
+        name: String,
+        stmts: Vec<ir::Stmt>,
+        defaults: Vec<(String, ir::Default)>,
+    ) -> ir::Def {
+        let rules = std::mem::take(&mut self.def_rules);
+        self.checkers
+            .extend(rules.iter().map(|rule| rule.check.checker()));
+        if !rules.is_empty() {
+            self.contracts.insert(name.clone());
+        }
+
+        ir::Def {
+            name,
+            class: self.class,
+            block: ir::Block::new(stmts),
+            defaults,
+            rules,
+        }
+    }
+
+    fn add_rule(&mut self, check: ir::Check) {
+        self.def_rules.push(ir::Rule {
+            path: self.path.clone(),
+            check,
+        });
+    }
+
+    /// Builds a value under the current path, e.g. a property or the elements of an array.
+    fn with_path<T>(&mut self, segment: ir::PathSegment, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.path.push(segment);
+        let built = f(self);
+        self.path.pop();
+
+        built
+    }
+
+    fn build_params_def(&mut self, name: String, params: &[ast::Schema]) -> ir::Def {
+        self.class = ir::SchemaClass::Params;
+        let mut stmts = vec![];
+        let mut defaults = vec![];
+        let gathered_keys = self.build_gathered_keys(params);
+        if !gathered_keys.is_empty() {
+            stmts.push(ir::Stmt::GatherKeys(gathered_keys));
         }
+        let coercions = self.build_coercions(params);
+        if !coercions.is_empty() {
+            stmts.push(ir::Stmt::Coerce(coercions));
+        }
+        let deprecated_names = params
+            .iter()
+            .filter(|param| param.deprecated)
+            .map(|param| param.name.clone())
+            .collect::<Vec<_>>();
+        if self.config.deprecated == DeprecatedPolicy::Log && !deprecated_names.is_empty() {
+            self.deprecations = true;
+            stmts.push(ir::Stmt::ReportDeprecated {
+                def_name: name.clone(),
+                names: deprecated_names,
+            });
+        }
+        for param in params {
+            let mut required = param.required;
+            if param.deprecated {
+                match self.config.deprecated {
+                    DeprecatedPolicy::Comment => {
+                        stmts.push(ir::Stmt::Comment(format!(
+                            "`{}` is deprecated.",
+                            param.name
+                        )));
+                    }
+                    DeprecatedPolicy::Log => (),
+                    DeprecatedPolicy::Reject => {
+                        self.with_path(ir::PathSegment::Key(param.name.clone()), |builder| {
+                            builder.add_rule(ir::Check::Forbidden(
+                                "must not be sent, since it is deprecated".to_string(),
+                            ))
+                        });
+                        required = false;
+                    }
+                }
+            }
+
+            // An empty JSON value such as `{}` of `content` is not an empty value of `allowEmptyValue`.
+            let presence = if param.nullable {
+                ir::Presence::Maybe
+            } else if param.allow_empty_value || param.serialization == ast::Serialization::Json {
+                ir::Presence::Value
+            } else {
+                ir::Presence::Filled
+            };
+            stmts.push(self.build_property(param.name.clone(), required, presence, &param.ty));
+            if let Some(default) = self.build_default(&param.ty, param.default.as_ref()) {
+                defaults.push((param.name.clone(), default));
+            }
+        }
+
+        self.build_def(name, stmts, defaults)
+    }
+
+    /// Rack leaves a serialized value as a string, so it is split before validation.
+    fn build_coercions(&mut self, params: &[ast::Schema]) -> Vec<(String, ir::Coercion)> {
+        let mut coercions = vec![];
+        for param in params {
+            let coercion = match param.serialization.clone() {
+                ast::Serialization::Native | ast::Serialization::Exploded { .. } => continue,
+                ast::Serialization::Prefixed { prefix } => ir::Coercion::StripPrefix(prefix),
+                ast::Serialization::Delimited { prefix, delimiter } => {
+                    ir::Coercion::Split { prefix, delimiter }
+                }
+                ast::Serialization::DelimitedPairs { prefix, delimiter } => {
+                    ir::Coercion::SplitPairs { prefix, delimiter }
+                }
+                ast::Serialization::KeyValues { prefix, delimiter } => {
+                    ir::Coercion::SplitKeyValues { prefix, delimiter }
+                }
+                ast::Serialization::Json => ir::Coercion::ParseJson,
+            };
+            self.coercers.insert(coercion.coercer());
+            coercions.push((param.name.clone(), coercion));
+        }
+
+        coercions
     }
 
-    fn build_item(&self, item: &ast::Type) -> ir::Macro {
+    fn build_gathered_keys(&mut self, params: &[ast::Schema]) -> Vec<(String, Vec<String>)> {
+        let gathered_keys = params
+            .iter()
+            .filter_map(|param| match &param.serialization {
+                ast::Serialization::Exploded { keys } if !keys.is_empty() => {
+                    Some((param.name.clone(), keys.clone()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !gathered_keys.is_empty() {
+            self.coercers.insert(ir::Coercer::GatherKeys);
+        }
+
+        gathered_keys
+    }
+
+    fn build_item(&mut self, item: &ast::Type) -> ir::Macro {
         match &item {
             ast::Type::Integer { validates } => ir::Macro::Each {
                 ty: ir::Type::Integer,
@@ -53,6 +318,11 @@ impl IrBuilder {
                 validates: self.build_validates(validates),
                 block: None,
             },
+            ast::Type::Decimal { validates } => ir::Macro::Each {
+                ty: ir::Type::Decimal,
+                validates: self.build_validates(validates),
+                block: None,
+            },
             ast::Type::String { validates } => ir::Macro::Each {
                 ty: ir::Type::String,
                 validates: self.build_validates(validates),
@@ -66,15 +336,28 @@ impl IrBuilder {
             ast::Type::Array { validates, item_ty } => ir::Macro::Each {
                 ty: ir::Type::Array,
                 validates: self.build_validates(validates),
-                block: item_ty.clone().map(|item_ty| {
-                    ir::Block::new_single_stmt({
-                        ir::Stmt::Schema {
-                            ty: ir::Type::Array,
-                            r#macro: self.build_item(&item_ty),
-                        }
+                block: item_ty.as_ref().map(|item_ty| {
+                    ir::Block::new_single_stmt(ir::Stmt::Schema {
+                        ty: ir::Type::Array,
+                        r#macro: self.with_path(ir::PathSegment::Each, |builder| {
+                            builder.build_item(item_ty)
+                        }),
                     })
                 }),
             },
+            ast::Type::Ref { name, lazy } => {
+                let name = self.named_schema_def_name(name);
+                if *lazy || self.contracts.contains(&name) {
+                    self.add_rule(ir::Check::NestedSchema(name));
+                    ir::Macro::Each {
+                        ty: ir::Type::Hash,
+                        validates: vec![],
+                        block: None,
+                    }
+                } else {
+                    ir::Macro::EachSchema { name }
+                }
+            }
             ast::Type::Object {
                 validates,
                 properties,
@@ -92,12 +375,37 @@ impl IrBuilder {
         }
     }
 
-    fn build_properties(&self, properties: &[ast::Property]) -> ir::Block {
+    fn build_properties(&mut self, properties: &[ast::Property]) -> ir::Block {
         let mut stmts = vec![];
         for property in properties {
+            let mut required = property.required;
+            match self.direction {
+                // A client must not send a `readOnly` property.
+                Direction::Request if property.read_only => match self.config.read_only {
+                    ReadOnlyPolicy::Ignore => continue,
+                    ReadOnlyPolicy::Reject => {
+                        self.with_path(ir::PathSegment::Key(property.key.clone()), |builder| {
+                            builder.add_rule(ir::Check::Forbidden(
+                                "must not be sent, since it is read-only".to_string(),
+                            ))
+                        });
+                        required = false;
+                    }
+                },
+                // A server never returns a `writeOnly` property.
+                Direction::Response if property.write_only => continue,
+                _ => (),
+            }
+
+            let presence = if property.nullable {
+                ir::Presence::Maybe
+            } else {
+                ir::Presence::Value
+            };
             stmts.push(self.build_property(
                 property.key.clone(),
-                property.required,
+                required,
+                presence,
                 &property.value,
             ));
         }
@@ -105,42 +413,70 @@ impl IrBuilder {
         ir::Block { stmts }
     }
 
-    fn build_property(&self, name: String, required: bool, ty: &ast::Type) -> ir::Stmt {
-        let r#macro = match ty {
+    fn build_property(
+        &mut self,
+        name: String,
+        required: bool,
+        presence: ir::Presence,
+        ty: &ast::Type,
+    ) -> ir::Stmt {
+        let segment = ir::PathSegment::Key(name.clone());
+        let r#macro = self.with_path(segment, |builder| builder.build_value(presence, ty));
+
+        if required {
+            ir::Stmt::Required { name, r#macro }
+        } else {
+            ir::Stmt::Optional { name, r#macro }
+        }
+    }
+
+    fn build_value(&mut self, presence: ir::Presence, ty: &ast::Type) -> ir::Macro {
+        match ty {
             ast::Type::Integer { validates } => ir::Macro::Value {
+                presence,
                 ty: ir::Type::Integer,
                 validates: self.build_validates(validates),
                 macro_or_block: None,
             },
             ast::Type::Number { validates } => ir::Macro::Value {
+                presence,
                 ty: ir::Type::Float,
                 validates: self.build_validates(validates),
                 macro_or_block: None,
             },
+            ast::Type::Decimal { validates } => ir::Macro::Value {
+                presence,
+                ty: ir::Type::Decimal,
+                validates: self.build_validates(validates),
+                macro_or_block: None,
+            },
             ast::Type::String { validates } => ir::Macro::Value {
+                presence,
                 ty: ir::Type::String,
                 validates: self.build_validates(validates),
                 macro_or_block: None,
             },
             ast::Type::Boolean => ir::Macro::Value {
+                presence,
                 ty: ir::Type::Boolean,
                 validates: vec![],
                 macro_or_block: None,
             },
             ast::Type::Array { validates, item_ty } => ir::Macro::Value {
+                presence,
                 ty: ir::Type::Array,
                 validates: self.build_validates(validates),
-                macro_or_block: if let Some(item) = item_ty {
-                    let item = *item.to_owned();
-                    Some(Box::new(ir::MacroOrBlock::Macro(self.build_item(&item))))
-                } else {
-                    None
-                },
+                macro_or_block: item_ty.as_ref().map(|item| {
+                    let item =
+                        self.with_path(ir::PathSegment::Each, |builder| builder.build_item(item));
+                    Box::new(ir::MacroOrBlock::Macro(item))
+                }),
             },
             ast::Type::Object {
                 validates: _,
                 properties,
             } => ir::Macro::Value {
+                presence,
                 ty: ir::Type::Hash,
                 validates: vec![],
                 macro_or_block: {
@@ -153,40 +489,297 @@ impl IrBuilder {
                     }
                 },
             },
+            ast::Type::Ref { name, lazy } => {
+                let name = self.named_schema_def_name(name);
+                // A contract, or a schema which may not be defined yet, is called by a rule.
+                if *lazy || self.contracts.contains(&name) {
+                    self.add_rule(ir::Check::NestedSchema(name));
+                    ir::Macro::Value {
+                        presence,
+                        ty: ir::Type::Hash,
+                        validates: vec![],
+                        macro_or_block: None,
+                    }
+                } else {
+                    ir::Macro::HashSchema { name, presence }
+                }
+            }
+        }
+    }
+
+    /// `Params` and `JSON` coerce differently, so each class refers to its own variant.
+    /// A named schema is shared by requests and responses unless it leaves out properties in either.
+    fn named_schema_def_name(&self, name: &str) -> String {
+        let direction = match self.variant_direction(self.direction, name) {
+            Direction::Request => " request",
+            Direction::Response => " response",
+            Direction::Shared => "",
+        };
+        let class = match self.class {
+            ir::SchemaClass::Params => " params",
+            ir::SchemaClass::Json => "",
         };
 
-        if required {
-            ir::Stmt::Required { name, r#macro }
-        } else {
-            ir::Stmt::Optional { name, r#macro }
+        format!("{name}{direction}{class} schema")
+    }
+
+    fn variant_direction(&self, direction: Direction, name: &str) -> Direction {
+        match direction {
+            Direction::Request if self.read_only_schemas.contains(name) => Direction::Request,
+            Direction::Response if self.write_only_schemas.contains(name) => Direction::Response,
+            _ => Direction::Shared,
         }
     }
 
-    fn build_validates(&self, validates: &[ast::Validate]) -> Vec<ir::Validate> {
-        let mut validates = validates
-            .iter()
-            .map(|validate| match validate {
-                ast::Validate::Max(max) => ir::Validate::Max(*max),
-                ast::Validate::Min(min) => ir::Validate::Min(*min),
-                ast::Validate::MaxF(max) => ir::Validate::MaxF(*max),
-                ast::Validate::MinF(min) => ir::Validate::MinF(*min),
-                ast::Validate::MaxLength(max) | ast::Validate::MaxItems(max) => {
-                    ir::Validate::MaxSize(*max)
+    /// Collects the variants of the named schemas to emit, each of which is emitted only if referred to,
+    /// except for the shared `JSON` one.
+    fn collect_variants(&mut self, ast: &ast::RootSchema) {
+        let named_schemas = self.named_schemas.clone();
+        for named_schema in &ast.named_schemas {
+            let name = &named_schema.name;
+            if has_property(
+                &named_schema.ty,
+                &named_schemas,
+                |p| p.read_only,
+                &mut HashSet::new(),
+            ) {
+                self.read_only_schemas.insert(name.clone());
+            }
+            if has_property(
+                &named_schema.ty,
+                &named_schemas,
+                |p| p.write_only,
+                &mut HashSet::new(),
+            ) {
+                self.write_only_schemas.insert(name.clone());
+            }
+        }
+
+        let json = ir::SchemaClass::Json;
+        for named_schema in &ast.named_schemas {
+            let variant = (named_schema.name.clone(), Direction::Shared, json);
+            self.variants.insert(variant);
+            self.collect_type_variants(&named_schema.ty, &named_schemas, Direction::Shared, json);
+        }
+        for message in &ast.messages {
+            let properties = &message.body.properties;
+            self.collect_properties_variants(properties, &named_schemas, Direction::Shared, json);
+        }
+        for operation in ast.path_items.iter().flat_map(|item| &item.operations) {
+            let params = operation
+                .queries
+                .iter()
+                .chain(&operation.path_params)
+                .chain(&operation.headers);
+            for param in params {
+                let params = ir::SchemaClass::Params;
+                self.collect_type_variants(&param.ty, &named_schemas, Direction::Request, params);
+            }
+            if let Some(request_body) = &operation.request_body {
+                let properties = &request_body.properties;
+                let class = body_class(request_body);
+                self.collect_properties_variants(
+                    properties,
+                    &named_schemas,
+                    Direction::Request,
+                    class,
+                );
+            }
+            for response in &operation.responses {
+                for header in &response.headers {
+                    let params = ir::SchemaClass::Params;
+                    self.collect_type_variants(
+                        &header.ty,
+                        &named_schemas,
+                        Direction::Response,
+                        params,
+                    );
                 }
-                ast::Validate::MinLength(min) | ast::Validate::MinItems(min) => {
-                    ir::Validate::MinSize(*min)
+                let Some(body) = &response.body else {
+                    continue;
+                };
+                let class = body_class(body);
+                self.collect_properties_variants(
+                    &body.properties,
+                    &named_schemas,
+                    Direction::Response,
+                    class,
+                );
+            }
+        }
+    }
+
+    fn collect_type_variants(
+        &mut self,
+        ty: &ast::Type,
+        named_schemas: &HashMap<&str, &ast::Type>,
+        direction: Direction,
+        class: ir::SchemaClass,
+    ) {
+        match ty {
+            ast::Type::Array {
+                item_ty: Some(item_ty),
+                ..
+            } => self.collect_type_variants(item_ty, named_schemas, direction, class),
+            ast::Type::Object { properties, .. } => {
+                self.collect_properties_variants(properties, named_schemas, direction, class)
+            }
+            ast::Type::Ref { name, .. } => {
+                let direction = self.variant_direction(direction, name);
+                if self.variants.insert((name.clone(), direction, class)) {
+                    if let Some(ty) = named_schemas.get(name.as_str()) {
+                        self.collect_type_variants(ty, named_schemas, direction, class);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Follows the properties as `build_properties` builds them in the direction.
+    fn collect_properties_variants(
+        &mut self,
+        properties: &[ast::Property],
+        named_schemas: &HashMap<&str, &ast::Type>,
+        direction: Direction,
+        class: ir::SchemaClass,
+    ) {
+        for property in properties {
+            match direction {
+                Direction::Request
+                    if property.read_only && self.config.read_only == ReadOnlyPolicy::Ignore =>
+                {
+                    continue
                 }
+                Direction::Response if property.write_only => continue,
+                _ => (),
+            }
+            self.collect_type_variants(&property.value, named_schemas, direction, class);
+        }
+    }
+
+    /// Collects the default of the value itself and the defaults of its nested properties.
+    /// The nested defaults are filled only into a given hash or into the value's own default,
+    /// so that they do not add a hash the client left out.
+    fn build_default(
+        &self,
+        ty: &ast::Type,
+        default: Option<&serde_json::Value>,
+    ) -> Option<ir::Default> {
+        let defaults = match ty {
+            ast::Type::Object { properties, .. } => self.build_properties_defaults(properties),
+            // A lazy reference is recursive, so its defaults would never end.
+            ast::Type::Ref { name, lazy: false } => match self.named_schemas.get(name.as_str()) {
+                Some(ty) => return self.build_default(ty, default),
+                None => vec![],
+            },
+            _ => vec![],
+        };
+        match default {
+            Some(serde_json::Value::Object(_)) | None if !defaults.is_empty() => {
+                Some(ir::Default::Nested {
+                    defaults,
+                    default: default.cloned(),
+                })
+            }
+            _ => default.cloned().map(ir::Default::Value),
+        }
+    }
+
+    fn build_properties_defaults(
+        &self,
+        properties: &[ast::Property],
+    ) -> Vec<(String, ir::Default)> {
+        properties
+            .iter()
+            .filter(|property| !property.read_only)
+            .filter_map(|property| {
+                let default = self.build_default(&property.value, property.default.as_ref())?;
+                Some((property.key.clone(), default))
+            })
+            .collect()
+    }
+
+    fn build_validates(&mut self, validates: &[ast::Validate]) -> Vec<ir::Validate> {
+        let mut validates = validates
+            .iter()
+            .filter_map(|validate| {
+                Some(match validate {
+                    ast::Validate::Max(max) => ir::Validate::Max(*max),
+                    ast::Validate::Min(min) => ir::Validate::Min(*min),
+                    ast::Validate::MaxF(max) => ir::Validate::MaxF(*max),
+                    ast::Validate::MinF(min) => ir::Validate::MinF(*min),
+                    ast::Validate::MaxLength(max) | ast::Validate::MaxItems(max) => {
+                        ir::Validate::MaxSize(*max)
+                    }
+                    ast::Validate::MinLength(min) | ast::Validate::MinItems(min) => {
+                        ir::Validate::MinSize(*min)
+                    }
+                    ast::Validate::UniqueItems => {
+                        // A rule, so that the message can tell where the duplicate is.
+                        self.add_rule(ir::Check::UniqueItems);
+                        return None;
+                    }
+                    ast::Validate::MaxDecimal(max) => ir::Validate::MaxDecimal(max.clone()),
+                    ast::Validate::MinDecimal(min) => ir::Validate::MinDecimal(min.clone()),
+                    ast::Validate::ExclusiveMaxDecimal(max) => {
+                        ir::Validate::LessThanDecimal(max.clone())
+                    }
+                    ast::Validate::ExclusiveMinDecimal(min) => {
+                        ir::Validate::GreaterThanDecimal(min.clone())
+                    }
+                    ast::Validate::Enum(values) => ir::Validate::IncludedIn(values.clone()),
+                })
             })
             .collect::<Vec<_>>();
         validates.sort_by_cached_key(|validate| match validate {
             ir::Validate::Min(_) => 0,
             ir::Validate::MinF(_) => 1,
-            ir::Validate::MinSize(_) => 2,
-            ir::Validate::Max(_) => 3,
-            ir::Validate::MaxF(_) => 4,
-            ir::Validate::MaxSize(_) => 5,
+            ir::Validate::MinDecimal(_) => 2,
+            ir::Validate::GreaterThanDecimal(_) => 3,
+            ir::Validate::MinSize(_) => 4,
+            ir::Validate::Max(_) => 5,
+            ir::Validate::MaxF(_) => 6,
+            ir::Validate::MaxDecimal(_) => 7,
+            ir::Validate::LessThanDecimal(_) => 8,
+            ir::Validate::MaxSize(_) => 9,
+            ir::Validate::IncludedIn(_) => 10,
         });
 
         validates
     }
 }
+
+/// A form is coerced from strings like parameters.
+fn body_class(body: &ast::Body) -> ir::SchemaClass {
+    if body.form {
+        ir::SchemaClass::Params
+    } else {
+        ir::SchemaClass::Json
+    }
+}
+
+/// Whether the type has a property matching the predicate, directly or through named schemas.
+fn has_property(
+    ty: &ast::Type,
+    named_schemas: &HashMap<&str, &ast::Type>,
+    predicate: fn(&ast::Property) -> bool,
+    visited: &mut HashSet<String>,
+) -> bool {
+    match ty {
+        ast::Type::Array {
+            item_ty: Some(item_ty),
+            ..
+        } => has_property(item_ty, named_schemas, predicate, visited),
+        ast::Type::Object { properties, .. } => properties.iter().any(|property| {
+            predicate(property) || has_property(&property.value, named_schemas, predicate, visited)
+        }),
+        ast::Type::Ref { name, .. } => {
+            visited.insert(name.clone())
+                && named_schemas
+                    .get(name.as_str())
+                    .is_some_and(|ty| has_property(ty, named_schemas, predicate, visited))
+        }
+        _ => false,
+    }
+}
@@ -1,26 +1,158 @@
 pub struct Defs {
+    pub coercers: Vec<Coercer>,
+    pub checkers: Vec<Checker>,
+    /// Whether the generated `Deprecations` module is used.
+    pub deprecations: bool,
     pub defs: Vec<Def>,
 }
 
+/// Helpers which reshape a raw value before dry-schema coerces it.
+/// They are emitted once into a generated `Coercions` module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Coercer {
+    /// Removes the prefix of a string.
+    StripPrefix,
+    /// Splits a string into an array.
+    Split,
+    /// Splits a string into a hash of alternating keys and values.
+    SplitPairs,
+    /// Splits a string into a hash of `key=value` pairs.
+    SplitKeyValues,
+    /// Parses a JSON text.
+    ParseJson,
+    /// Gathers keys into a hash.
+    GatherKeys,
+}
+
+/// How the value of a key is reshaped.
+/// `prefix` is written before the value, which is removed before splitting.
+pub enum Coercion {
+    StripPrefix(String),
+    Split { prefix: String, delimiter: String },
+    SplitPairs { prefix: String, delimiter: String },
+    SplitKeyValues { prefix: String, delimiter: String },
+    ParseJson,
+}
+impl Coercion {
+    pub fn coercer(&self) -> Coercer {
+        match self {
+            Coercion::StripPrefix(_) => Coercer::StripPrefix,
+            Coercion::Split { .. } => Coercer::Split,
+            Coercion::SplitPairs { .. } => Coercer::SplitPairs,
+            Coercion::SplitKeyValues { .. } => Coercer::SplitKeyValues,
+            Coercion::ParseJson => Coercer::ParseJson,
+        }
+    }
+}
+
+/// Helpers which check what dry-schema cannot, such as a check whose message depends on the value.
+/// They are emitted once into a generated `Rules` module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Checker {
+    /// Fails for an array with a duplicate, with the index of it.
+    UniqueItems,
+    /// Fails whenever the key is present.
+    Forbidden,
+    /// Validates a hash with another definition, and adds its errors under the path.
+    NestedSchema,
+}
+
+pub enum Check {
+    UniqueItems,
+    /// Fails with the message.
+    Forbidden(String),
+    /// Validates with the definition of the name.
+    NestedSchema(String),
+}
+impl Check {
+    pub fn checker(&self) -> Checker {
+        match self {
+            Check::UniqueItems => Checker::UniqueItems,
+            Check::Forbidden(_) => Checker::Forbidden,
+            Check::NestedSchema(_) => Checker::NestedSchema,
+        }
+    }
+}
+
+/// A check of the values at the path.
+pub struct Rule {
+    pub path: Vec<PathSegment>,
+    pub check: Check,
+}
+
+pub enum Default {
+    Value(serde_json::Value),
+    /// The defaults of the keys of a hash, which are filled only if the hash is given,
+    /// or into the default of the hash itself.
+    Nested {
+        defaults: Vec<(String, Default)>,
+        default: Option<serde_json::Value>,
+    },
+}
+
+#[derive(Clone)]
+pub enum PathSegment {
+    Key(String),
+    /// Every element of an array.
+    Each,
+}
+
 pub struct Def {
     pub name: String,
     pub class: SchemaClass,
     pub block: Block,
+    /// Default values keyed by parameter name, since dry-schema does not fill them by itself.
+    pub defaults: Vec<(String, Default)>,
+    /// A definition with rules is a contract of dry-validation, which runs them after the schema.
+    pub rules: Vec<Rule>,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SchemaClass {
     Params,
+    Json,
 }
 
 pub enum Stmt {
-    Required { name: String, r#macro: Macro },
-    Optional { name: String, r#macro: Macro },
-    Schema { ty: Type, r#macro: Macro },
+    Required {
+        name: String,
+        r#macro: Macro,
+    },
+    Optional {
+        name: String,
+        r#macro: Macro,
+    },
+    Schema {
+        ty: Type,
+        r#macro: Macro,
+    },
+    /// Reshapes the values of the keys before they are coerced, keyed by name.
+    Coerce(Vec<(String, Coercion)>),
+    /// Gathers the keys of exploded objects into their names before the keys are coerced.
+    GatherKeys(Vec<(String, Vec<String>)>),
+    /// Reports the deprecated keys which are present, with the name of the definition.
+    ReportDeprecated {
+        def_name: String,
+        names: Vec<String>,
+    },
+    Comment(String),
+}
+
+/// What a key accepts besides the type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presence {
+    /// Any value of the type, including an empty one such as `""`.
+    Value,
+    /// A value of the type which is not empty.
+    Filled,
+    /// `nil` or a value of the type.
+    Maybe,
 }
 
 pub enum Macro {
     Value {
         ty: Type,
+        presence: Presence,
         validates: Vec<Validate>,
         macro_or_block: Option<Box<MacroOrBlock>>,
     },
@@ -29,6 +161,10 @@ pub enum Macro {
         validates: Vec<Validate>,
         block: Option<Block>,
     },
+    /// A hash validated with the definition of the name.
+    HashSchema { name: String, presence: Presence },
+    /// Each element validated with the definition of the name.
+    EachSchema { name: String },
 }
 
 pub enum MacroOrBlock {
@@ -56,11 +192,19 @@ pub enum Validate {
     MinF(f64),
     MaxSize(usize),
     MinSize(usize),
+    /// Decimal bounds are held as the literal text to avoid float rounding.
+    MaxDecimal(String),
+    MinDecimal(String),
+    LessThanDecimal(String),
+    GreaterThanDecimal(String),
+    /// One of the values.
+    IncludedIn(Vec<serde_json::Value>),
 }
 
 pub enum Type {
     Integer,
     Float,
+    Decimal,
     String,
     Boolean,
     Array,
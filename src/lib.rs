@@ -1,45 +1,200 @@
 mod ast_builder;
+mod bundler;
 mod codegen;
+mod config;
+mod frontend;
 mod ir_builder;
+mod loader;
 
-use std::{fs::File, io::Read, path::Path};
+pub use config::{
+    CacheConfig, Config, DeprecatedPolicy, HostHeader, HttpConfig, ReadOnlyPolicy, ResolutionPolicy,
+};
+pub use loader::{CachingLoader, DefaultLoader, DocumentLoader, FileSystemLoader, HttpLoader};
+
+use std::path::Path;
 
 use anyhow::Result;
 use openapiv3::OpenAPI;
+use url::Url;
+
+/// Generated code and the problems found while generating it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generated {
+    pub code: String,
+    /// Parts of the input which are skipped, e.g. references which could not be resolved.
+    pub diagnostics: Vec<String>,
+}
+impl Generated {
+    /// Nothing is generated, and the error is the only diagnostic.
+    fn failed(err: anyhow::Error) -> Self {
+        Self {
+            code: String::new(),
+            diagnostics: vec![format!("Failed to set up the loader. error: {err:#}")],
+        }
+    }
+}
 
 pub fn generate_dry_validation_from_root_file<P>(path: P) -> String
+where
+    P: AsRef<Path>,
+{
+    generate_dry_validation_from_root_file_with_config(path, &Config::default()).code
+}
+
+pub fn generate_dry_validation_from_root_file_with_config<P>(path: P, config: &Config) -> Generated
 where
     P: AsRef<Path>,
 {
     let file_type = select_file_type(&path).unwrap();
 
-    let file_content = {
-        let mut file = File::open(path).unwrap();
-        let mut buf = String::new();
-        file.read_to_string(&mut buf).unwrap();
+    // References in the file are relative to the file.
+    let base = Url::from_file_path(std::path::absolute(path).unwrap()).unwrap();
+    let loader = match loader::loader_of(config) {
+        Ok(loader) => loader,
+        Err(err) => return Generated::failed(err),
+    };
+    let file_content = loader.load(&base).unwrap();
+
+    let document = frontend::parse(&file_content, file_type);
+    generate_dry_validation_from_root(&document, &base, config, loader.as_ref())
+}
+
+/// A self-contained document and the problems found while bundling it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bundled {
+    /// The document in the same format as the input.
+    pub document: String,
+    /// References which are kept as they are, because they could not be bundled.
+    pub diagnostics: Vec<String>,
+}
 
-        buf
+pub fn bundle_root_file<P>(path: P) -> String
+where
+    P: AsRef<Path>,
+{
+    bundle_root_file_with_config(path, &Config::default()).document
+}
+
+/// Writes the file as one document, with external references copied into `components`.
+pub fn bundle_root_file_with_config<P>(path: P, config: &Config) -> Bundled
+where
+    P: AsRef<Path>,
+{
+    let file_type = select_file_type(&path).unwrap();
+
+    let base = Url::from_file_path(std::path::absolute(path).unwrap()).unwrap();
+    let loader = match loader::loader_of(config) {
+        Ok(loader) => loader,
+        Err(err) => {
+            return Bundled {
+                document: String::new(),
+                diagnostics: vec![format!("Failed to set up the loader. error: {err:#}")],
+            }
+        }
+    };
+    let file_content = loader.load(&base).unwrap();
+
+    // Bundled as JSON values rather than `OpenAPI`, so that nothing in the input is dropped.
+    let root: serde_json::Value = match file_type {
+        SupportFileType::Json => serde_json::from_str(&file_content).unwrap(),
+        SupportFileType::Yaml => {
+            let root: serde_yaml::Value = serde_yaml::from_str(&file_content).unwrap();
+            serde_json::to_value(root).unwrap()
+        }
+    };
+    let result = bundler::bundle(root, &base, config, loader.as_ref());
+    let document = match file_type {
+        SupportFileType::Json => {
+            let mut document = serde_json::to_string_pretty(&result.document).unwrap();
+            document.push('\n');
+            document
+        }
+        SupportFileType::Yaml => serde_yaml::to_string(&result.document).unwrap(),
     };
 
-    match file_type {
-        SupportFileType::Json => generate_dry_validation_from_root_json(&file_content),
-        SupportFileType::Yaml => generate_dry_validation_from_root_yaml(&file_content),
+    Bundled {
+        document,
+        diagnostics: result.errors,
     }
 }
 
+pub fn generate_dry_validation_from_json_schema_file<P>(path: P) -> String
+where
+    P: AsRef<Path>,
+{
+    generate_dry_validation_from_json_schema_file_with_config(path, &Config::default()).code
+}
+
+/// Generates a `Dry::Schema::JSON` for the root of a JSON Schema document(draft-07 or 2020-12)
+/// and each of its `definitions` or `$defs`.
+/// The root is named after `title`, or the file name if it has no title.
+pub fn generate_dry_validation_from_json_schema_file_with_config<P>(
+    path: P,
+    config: &Config,
+) -> Generated
+where
+    P: AsRef<Path>,
+{
+    let file_type = select_file_type(&path).unwrap();
+
+    let base = Url::from_file_path(std::path::absolute(&path).unwrap()).unwrap();
+    let loader = match loader::loader_of(config) {
+        Ok(loader) => loader,
+        Err(err) => return Generated::failed(err),
+    };
+    let file_content = loader.load(&base).unwrap();
+
+    // `event.schema.json` is named `event`.
+    let file_name = path.as_ref().file_name().unwrap().to_string_lossy();
+    let root_name = file_name.split('.').next().unwrap();
+    let document = frontend::parse_json_schema(&file_content, file_type, root_name);
+    generate_dry_validation_from_root(&document, &base, config, loader.as_ref())
+}
+
 pub fn generate_dry_validation_from_root_json(text: &str) -> String {
-    let openapi: OpenAPI = match serde_json::from_str(text) {
+    generate_dry_validation_from_root_json_with_config(text, &Config::default()).code
+}
+
+pub fn generate_dry_validation_from_root_json_with_config(
+    text: &str,
+    config: &Config,
+) -> Generated {
+    let document = frontend::parse(text, SupportFileType::Json);
+    let loader = match loader::loader_of(config) {
+        Ok(loader) => loader,
+        Err(err) => return Generated::failed(err),
+    };
+    generate_dry_validation_from_root(&document, &current_dir_url(), config, loader.as_ref())
+}
+
+pub fn generate_dry_validation_from_root_yaml(text: &str) -> String {
+    generate_dry_validation_from_root_yaml_with_config(text, &Config::default()).code
+}
+
+pub fn generate_dry_validation_from_root_yaml_with_config(
+    text: &str,
+    config: &Config,
+) -> Generated {
+    let document = frontend::parse(text, SupportFileType::Yaml);
+    let loader = match loader::loader_of(config) {
+        Ok(loader) => loader,
+        Err(err) => return Generated::failed(err),
+    };
+    generate_dry_validation_from_root(&document, &current_dir_url(), config, loader.as_ref())
+}
+
+fn parse_json(text: &str) -> OpenAPI {
+    match serde_json::from_str(text) {
         Ok(openapi) => openapi,
         Err(err) => panic!(
             "Could not deserialize input\nerror line: `{}`\n",
             text.lines().nth(err.line()).unwrap().trim()
         ),
-    };
-    generate_dry_validation_from_root(&openapi)
+    }
 }
 
-pub fn generate_dry_validation_from_root_yaml(text: &str) -> String {
-    let openapi: OpenAPI = match serde_yaml::from_str(text) {
+fn parse_yaml(text: &str) -> OpenAPI {
+    match serde_yaml::from_str(text) {
         Ok(openapi) => openapi,
         Err(err) => panic!(
             "Could not deserialize input\nerror line: `{}`\n",
@@ -48,10 +203,15 @@ pub fn generate_dry_validation_from_root_yaml(text: &str) -> String {
                 .unwrap()
                 .trim()
         ),
-    };
-    generate_dry_validation_from_root(&openapi)
+    }
 }
 
+/// References in a text input are relative to the working directory.
+fn current_dir_url() -> Url {
+    Url::from_directory_path(std::env::current_dir().unwrap()).unwrap()
+}
+
+#[derive(Clone, Copy)]
 enum SupportFileType {
     Json,
     Yaml,
@@ -71,12 +231,19 @@ where
     }
 }
 
-fn generate_dry_validation_from_root(openapi: &OpenAPI) -> String {
-    let mut code = String::new();
-
-    let ast_result = ast_builder::build(openapi);
-    let ir_result = ir_builder::build(&ast_result.ast);
+fn generate_dry_validation_from_root(
+    document: &frontend::Document,
+    base: &Url,
+    config: &Config,
+    loader: &dyn DocumentLoader,
+) -> Generated {
+    let ast_result = ast_builder::build(document, base, config, loader);
+    let ir_result = ir_builder::build(&ast_result.ast, config);
 
-    code += &codegen::generate(&ir_result.ir);
-    code
+    let mut diagnostics = document.diagnostics.clone();
+    diagnostics.extend(ast_result.errors);
+    Generated {
+        code: codegen::generate(&ir_result.ir),
+        diagnostics,
+    }
 }
@@ -0,0 +1,197 @@
+mod cache;
+
+pub use cache::CachingLoader;
+
+use std::{fmt, io::Read, sync::Arc};
+
+use anyhow::Result;
+use url::Url;
+
+use crate::{Config, HttpConfig};
+
+/// Loads the content of a document referred to by `$ref`.
+///
+/// Implement this to read documents from somewhere other than the file system or HTTP,
+/// e.g. an in-memory file system or a git repository, and set it to `Config::loader`.
+pub trait DocumentLoader: fmt::Debug + Send + Sync {
+    fn load(&self, url: &Url) -> Result<String>;
+}
+
+/// Loads `file` URLs from the file system.
+#[derive(Debug, Clone, Default)]
+pub struct FileSystemLoader {
+    /// Files larger than this in bytes are not read.
+    pub max_document_size: Option<usize>,
+}
+impl DocumentLoader for FileSystemLoader {
+    fn load(&self, url: &Url) -> Result<String> {
+        let path = url
+            .to_file_path()
+            .map_err(|_| anyhow::anyhow!("Invalid file path.(url: {url})"))?;
+        if let Some(max_document_size) = self.max_document_size {
+            let size = std::fs::metadata(&path)?.len();
+            check_document_size(url, size, max_document_size)?;
+        }
+        Ok(std::fs::read_to_string(path)?)
+    }
+}
+
+/// Loads `http` and `https` URLs over HTTP.
+#[derive(Debug, Clone)]
+pub struct HttpLoader {
+    client: reqwest::blocking::Client,
+    config: HttpConfig,
+    max_document_size: Option<usize>,
+}
+impl HttpLoader {
+    /// Responses larger than `max_document_size` in bytes are rejected without reading them all.
+    pub fn new(config: &HttpConfig, max_document_size: Option<usize>) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder()
+            .connect_timeout(config.connect_timeout)
+            .timeout(config.timeout)
+            .build()?;
+
+        Ok(Self {
+            client,
+            config: config.clone(),
+            max_document_size,
+        })
+    }
+
+    fn fetch(&self, url: &Url) -> Result<FetchResult> {
+        let mut request = self.client.get(url.as_str());
+        for header in &self.config.headers {
+            if is_header_host(&header.host, url) {
+                request = request.header(&header.name, &header.value);
+            }
+        }
+
+        let response = match request.send() {
+            Ok(response) => response,
+            Err(err) => return Ok(FetchResult::Retryable(err.into())),
+        };
+        let status = response.status();
+        if status.is_success() {
+            Ok(FetchResult::Fetched(self.read_body(url, response)?))
+        } else {
+            let err = anyhow::anyhow!("Failed to fetch.(url: {url}, status: {status})");
+            if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                Ok(FetchResult::Retryable(err))
+            } else {
+                Err(err)
+            }
+        }
+    }
+
+    fn read_body(&self, url: &Url, response: reqwest::blocking::Response) -> Result<String> {
+        let Some(max_document_size) = self.max_document_size else {
+            return Ok(response.text()?);
+        };
+        if let Some(size) = response.content_length() {
+            check_document_size(url, size, max_document_size)?;
+        }
+
+        // Content-Length may be missing or wrong, so the body is read one byte past the limit.
+        let mut body = Vec::new();
+        response
+            .take(max_document_size as u64 + 1)
+            .read_to_end(&mut body)?;
+        if body.len() > max_document_size {
+            anyhow::bail!(
+                "The document is too large.(url: {url}, size: more than {max_document_size}, max: {max_document_size})"
+            );
+        }
+        Ok(String::from_utf8(body)?)
+    }
+}
+impl DocumentLoader for HttpLoader {
+    fn load(&self, url: &Url) -> Result<String> {
+        let mut backoff = self.config.retry_backoff;
+        for _ in 0..self.config.retries {
+            match self.fetch(url)? {
+                FetchResult::Fetched(content) => return Ok(content),
+                FetchResult::Retryable(_) => {
+                    std::thread::sleep(backoff);
+                    backoff *= 2;
+                }
+            }
+        }
+
+        match self.fetch(url)? {
+            FetchResult::Fetched(content) => Ok(content),
+            FetchResult::Retryable(err) => Err(err),
+        }
+    }
+}
+
+enum FetchResult {
+    Fetched(String),
+    Retryable(anyhow::Error),
+}
+
+/// Whether the host of a header matches the URL, with or without the port.
+/// An IPv6 host may be written with or without brackets, e.g. `[::1]` or `::1`.
+fn is_header_host(host: &str, url: &Url) -> bool {
+    let Some(url_host) = url.host_str() else {
+        return false;
+    };
+    let unbracketed = url_host.trim_start_matches('[').trim_end_matches(']');
+    if host == url_host || host == unbracketed {
+        return true;
+    }
+
+    url.port()
+        .is_some_and(|port| host == format!("{url_host}:{port}"))
+}
+
+/// Loads a document with `FileSystemLoader` or `HttpLoader` by the scheme of the URL.
+/// Remote documents are cached with `CachingLoader` if `Config::cache` is set.
+#[derive(Debug, Clone)]
+pub struct DefaultLoader {
+    file_system: FileSystemLoader,
+    http: Arc<dyn DocumentLoader>,
+}
+impl DefaultLoader {
+    pub fn new(config: &Config) -> Result<Self> {
+        let max_document_size = config.resolution.max_document_size;
+        let http = HttpLoader::new(&config.http, max_document_size)?;
+        let http: Arc<dyn DocumentLoader> = match &config.cache {
+            Some(cache) => Arc::new(CachingLoader::new(http, cache)?),
+            None => Arc::new(http),
+        };
+
+        Ok(Self {
+            file_system: FileSystemLoader { max_document_size },
+            http,
+        })
+    }
+}
+impl DocumentLoader for DefaultLoader {
+    fn load(&self, url: &Url) -> Result<String> {
+        match url.scheme() {
+            "file" => self.file_system.load(url),
+            "https" | "http" => self.http.load(url),
+            scheme => anyhow::bail!("Unsupported scheme.(scheme: {scheme})"),
+        }
+    }
+}
+
+/// Fails if the document is larger than the limit.
+pub(crate) fn check_document_size(url: &Url, size: u64, max_document_size: usize) -> Result<()> {
+    if size > max_document_size as u64 {
+        anyhow::bail!(
+            "The document is too large.(url: {url}, size: {size}, max: {max_document_size})"
+        );
+    }
+
+    Ok(())
+}
+
+/// The loader of the config, or `DefaultLoader` if it is not set.
+/// Fails if `DefaultLoader` can not be set up, e.g. the lockfile is broken.
+pub(crate) fn loader_of(config: &Config) -> Result<Arc<dyn DocumentLoader>> {
+    Ok(match &config.loader {
+        Some(loader) => Arc::clone(loader),
+        None => Arc::new(DefaultLoader::new(config)?),
+    })
+}
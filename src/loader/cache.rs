@@ -0,0 +1,108 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::Mutex};
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use super::DocumentLoader;
+use crate::CacheConfig;
+
+/// Caches the documents of another loader on disk, and records their hashes in a lockfile.
+#[derive(Debug)]
+pub struct CachingLoader<L> {
+    inner: L,
+    config: CacheConfig,
+    /// Content hashes by URL.
+    lock: Mutex<BTreeMap<String, String>>,
+}
+impl<L: DocumentLoader> CachingLoader<L> {
+    pub fn new(inner: L, config: &CacheConfig) -> Result<Self> {
+        let lock = if config.lockfile.exists() {
+            let path = config.lockfile.display();
+            let content = std::fs::read_to_string(&config.lockfile).map_err(|err| {
+                anyhow::anyhow!("Failed to read the lockfile.(path: {path}, error: {err})")
+            })?;
+            serde_json::from_str(&content).map_err(|err| {
+                anyhow::anyhow!("The lockfile is broken.(path: {path}, error: {err})")
+            })?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            inner,
+            config: config.clone(),
+            lock: Mutex::new(lock),
+        })
+    }
+
+    fn cache_path(&self, url: &Url) -> PathBuf {
+        self.config.dir.join(hash(url.as_str()))
+    }
+
+    fn read_cache(&self, url: &Url) -> Result<String> {
+        let path = self.cache_path(url);
+        if !path.exists() {
+            anyhow::bail!("Not cached in offline mode.(url: {url})");
+        }
+
+        Ok(std::fs::read_to_string(path)?)
+    }
+
+    fn write_cache(&self, url: &Url, content: &str) -> Result<()> {
+        std::fs::create_dir_all(&self.config.dir)?;
+        std::fs::write(self.cache_path(url), content)?;
+
+        Ok(())
+    }
+
+    fn check_and_lock(&self, url: &Url, content: &str) -> Result<()> {
+        let content_hash = format!("sha256:{}", hash(content));
+        let mut lock = self.lock.lock().unwrap();
+        match lock.get(url.as_str()) {
+            Some(locked) if *locked == content_hash => return Ok(()),
+            Some(locked) if self.config.frozen => anyhow::bail!(
+                "Hash of the document has changed.(url: {url}, locked: {locked}, actual: {content_hash})"
+            ),
+            None if self.config.frozen => {
+                anyhow::bail!("The document is not in the lockfile.(url: {url})")
+            }
+            Some(locked) if self.config.offline => anyhow::bail!(
+                "Hash of the cached document differs from the lockfile.(url: {url}, locked: {locked}, actual: {content_hash})"
+            ),
+            _ => (),
+        }
+        // Nothing is fetched in offline mode, so there is nothing new to lock.
+        if self.config.offline {
+            return Ok(());
+        }
+
+        lock.insert(url.to_string(), content_hash);
+        std::fs::write(
+            &self.config.lockfile,
+            serde_json::to_string_pretty(&*lock)? + "\n",
+        )?;
+
+        Ok(())
+    }
+}
+impl<L: DocumentLoader> DocumentLoader for CachingLoader<L> {
+    fn load(&self, url: &Url) -> Result<String> {
+        if self.config.offline {
+            let content = self.read_cache(url)?;
+            self.check_and_lock(url, &content)?;
+            return Ok(content);
+        }
+
+        let content = self.inner.load(url)?;
+        // Checked before caching, so that a changed document does not replace the locked one.
+        self.check_and_lock(url, &content)?;
+        self.write_cache(url, &content)?;
+
+        Ok(content)
+    }
+}
+
+fn hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
@@ -2,30 +2,241 @@ use std::{
     fs::File,
     io::Write,
     path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
-use openapi_dry_validation_generator::generate_dry_validation_from_root_file;
+use openapi_dry_validation_generator::{
+    bundle_root_file_with_config, generate_dry_validation_from_json_schema_file_with_config,
+    generate_dry_validation_from_root_file_with_config, CacheConfig, Config, DefaultLoader,
+    DeprecatedPolicy, HostHeader, HttpConfig, ReadOnlyPolicy, ResolutionPolicy,
+};
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    #[arg(short, long)]
-    input: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(short, long, required = true)]
+    input: Option<String>,
 
     #[arg(short, long, default_value = "out")]
     output: String,
+
+    /// How `readOnly` properties sent by a client are treated.
+    #[arg(long, value_enum, default_value_t = ReadOnlyPolicy::Ignore)]
+    read_only: ReadOnlyPolicy,
+
+    /// How `deprecated` parameters are treated.
+    #[arg(long, value_enum, default_value_t = DeprecatedPolicy::Comment)]
+    deprecated: DeprecatedPolicy,
+
+    /// Expand `$ref`s to `components/schemas` in place instead of emitting named schemas.
+    #[arg(long)]
+    inline_schemas: bool,
+
+    /// Bound `format: int64` integers to the range of int64.
+    #[arg(long)]
+    int64_bounds: bool,
+
+    /// Read the input as a JSON Schema document instead of OpenAPI.
+    #[arg(long)]
+    json_schema: bool,
+
+    #[command(flatten)]
+    load: LoadArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write the input as one document, with external `$ref`s copied into `components`.
+    Bundle {
+        #[arg(short, long)]
+        input: String,
+
+        /// File to write the document to. [default: stdout]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        #[command(flatten)]
+        load: LoadArgs,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct LoadArgs {
+    /// Header sent when fetching a remote `$ref` from the host, as `<host>=<name>:<value>`.
+    /// Multiple headers in the environment variable are separated by `;`.
+    #[arg(
+        long = "header",
+        env = "OPENAPI_DRY_VALIDATION_HEADERS",
+        value_delimiter = ';',
+        hide_env_values = true
+    )]
+    headers: Vec<HostHeader>,
+
+    /// Seconds to wait for connecting to the host of a remote `$ref`.
+    #[arg(long)]
+    connect_timeout: Option<u64>,
+
+    /// Seconds to wait for the response of a remote `$ref`.
+    #[arg(long)]
+    timeout: Option<u64>,
+
+    /// How many times fetching a remote `$ref` is retried.
+    #[arg(long)]
+    retries: Option<u32>,
+
+    /// Cache remote `$ref` documents, and lock their hashes.
+    #[arg(long)]
+    cache: bool,
+
+    /// Directory where remote `$ref` documents are cached.
+    /// [default: `.openapi-cache` next to the input]
+    #[arg(long, requires = "cache")]
+    cache_dir: Option<PathBuf>,
+
+    /// File recording the hashes of remote `$ref` documents.
+    /// [default: `<input stem>.lock.json` next to the input]
+    #[arg(long, requires = "cache")]
+    lockfile: Option<PathBuf>,
+
+    /// Resolve remote `$ref`s only from the cache.
+    #[arg(long, requires = "cache")]
+    offline: bool,
+
+    /// Fail if a remote `$ref` document is not locked or has changed since it was locked.
+    #[arg(long, requires = "cache")]
+    frozen: bool,
+
+    /// Only load local `$ref` documents inside the directory.
+    #[arg(long)]
+    root_dir: Option<PathBuf>,
+
+    /// Only load `$ref` documents of the schemes. [default: file, http, https]
+    #[arg(long = "allow-scheme")]
+    allowed_schemes: Vec<String>,
+
+    /// Only load remote `$ref` documents from the hosts. [default: any host]
+    #[arg(long = "allow-host")]
+    allowed_hosts: Vec<String>,
+
+    /// Maximum size of a `$ref` document in bytes.
+    #[arg(long)]
+    max_document_size: Option<usize>,
+
+    /// Maximum number of `$ref` documents loaded.
+    #[arg(long)]
+    max_documents: Option<usize>,
+}
+impl LoadArgs {
+    /// Fails if the loader can not be set up, e.g. the lockfile is broken.
+    fn config(self, input: &str) -> Result<Config> {
+        let mut http = HttpConfig {
+            headers: self.headers,
+            ..HttpConfig::default()
+        };
+        if let Some(connect_timeout) = self.connect_timeout {
+            http.connect_timeout = Duration::from_secs(connect_timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            http.timeout = Duration::from_secs(timeout);
+        }
+        if let Some(retries) = self.retries {
+            http.retries = retries;
+        }
+        let input_dir = Path::new(input).parent().unwrap_or(Path::new(""));
+        let cache = self.cache.then(|| CacheConfig {
+            dir: self
+                .cache_dir
+                .unwrap_or_else(|| input_dir.join(".openapi-cache")),
+            lockfile: self.lockfile.unwrap_or_else(|| {
+                let stem = Path::new(input).file_stem().unwrap().to_string_lossy();
+                input_dir.join(format!("{stem}.lock.json"))
+            }),
+            offline: self.offline,
+            frozen: self.frozen,
+        });
+        let mut resolution = ResolutionPolicy {
+            root_dir: self.root_dir,
+            max_document_size: self.max_document_size,
+            max_documents: self.max_documents,
+            ..ResolutionPolicy::default()
+        };
+        if !self.allowed_schemes.is_empty() {
+            resolution.allowed_schemes = self.allowed_schemes;
+        }
+        if !self.allowed_hosts.is_empty() {
+            resolution.allowed_hosts = Some(self.allowed_hosts);
+        }
+
+        let config = Config {
+            http,
+            cache,
+            resolution,
+            ..Config::default()
+        };
+        let loader = DefaultLoader::new(&config)?;
+
+        Ok(Config {
+            loader: Some(Arc::new(loader)),
+            ..config
+        })
+    }
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let ruby_code = generate_dry_validation_from_root_file(&args.input);
+    if let Some(Command::Bundle {
+        input,
+        output,
+        load,
+    }) = args.command
+    {
+        let config = load.config(&input)?;
+        let bundled = bundle_root_file_with_config(&input, &config);
+        for diagnostic in &bundled.diagnostics {
+            eprintln!("warning: {diagnostic}");
+        }
+        match output {
+            Some(output) => std::fs::write(output, &bundled.document).unwrap(),
+            None => print!("{}", bundled.document),
+        }
+
+        return Ok(());
+    }
+
+    let input = args.input.unwrap();
+    let config = Config {
+        read_only: args.read_only,
+        deprecated: args.deprecated,
+        inline_schemas: args.inline_schemas,
+        int64_bounds: args.int64_bounds,
+        ..args.load.config(&input)?
+    };
+    let generated = if args.json_schema {
+        generate_dry_validation_from_json_schema_file_with_config(&input, &config)
+    } else {
+        generate_dry_validation_from_root_file_with_config(&input, &config)
+    };
+    for diagnostic in &generated.diagnostics {
+        eprintln!("warning: {diagnostic}");
+    }
 
-    let output = Output::new(&args.output, &args.input).unwrap();
+    let output = Output::new(&args.output, &input).unwrap();
     output.create_dir_all().unwrap();
-    output.write_file_all(&ruby_code).unwrap();
+    output.write_file_all(&generated.code).unwrap();
 
     Ok(())
 }
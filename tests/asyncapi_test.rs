@@ -0,0 +1,167 @@
+mod common;
+
+use expect_test::{expect, Expect};
+use openapi_dry_validation_generator::{
+    generate_dry_validation_from_root_yaml_with_config, Config,
+};
+
+fn check(actual: &str, expect: Expect) {
+    let generated = generate_dry_validation_from_root_yaml_with_config(actual, &Config::default());
+    expect.assert_eq(&common::with_diagnostics(generated));
+}
+
+#[test]
+fn channel_messages() {
+    check(
+        r##"
+            asyncapi: 2.6.0
+            info:
+              title: Account events
+              version: 1.0.0
+            channels:
+              user/signedup:
+                subscribe:
+                  operationId: onUserSignedUp
+                  message:
+                    $ref: "#/components/messages/UserSignedUp"
+              user/deleted:
+                publish:
+                  operationId: publishUserDeleted
+                  message:
+                    payload:
+                      type: object
+                      required: [id]
+                      properties:
+                        id:
+                          type: string
+                        reason:
+                          type: [string, "null"]
+              user/renamed:
+                subscribe:
+                  message:
+                    oneOf:
+                      - $ref: "#/components/messages/UserSignedUp"
+                      - messageId: userRenamed
+                        payload:
+                          type: object
+                          properties:
+                            name:
+                              type: string
+            components:
+              messages:
+                UserSignedUp:
+                  payload:
+                    $ref: "#/components/schemas/User"
+              schemas:
+                User:
+                  type: object
+                  required: [email]
+                  properties:
+                    email:
+                      type: string
+                    address:
+                      $ref: "#/components/schemas/Address"
+                Address:
+                  type: object
+                  properties:
+                    city:
+                      type: string
+        "##,
+        expect![[r#"
+            AddressSchema = Dry::Schema::JSON do
+              optional(:city).value(:string)
+            end
+            UserSchema = Dry::Schema::JSON do
+              required(:email).value(:string)
+              optional(:address).hash(AddressSchema)
+            end
+            UserSignedUp = Dry::Schema::JSON do
+              required(:email).value(:string)
+              optional(:address).hash(AddressSchema)
+            end
+            PublishUserDeleted = Dry::Schema::JSON do
+              required(:id).value(:string)
+              optional(:reason).maybe(:string)
+            end
+            UserRenamed = Dry::Schema::JSON do
+              optional(:name).value(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn unsupported_schema_format() {
+    check(
+        r##"
+            asyncapi: 2.6.0
+            info:
+              title: Account events
+              version: 1.0.0
+            channels:
+              user/signedup:
+                subscribe:
+                  message:
+                    name: userSignedUp
+                    schemaFormat: application/vnd.apache.avro;version=1.9.0
+                    payload:
+                      type: record
+                      name: User
+                      fields:
+                        - name: email
+                          type: string
+        "##,
+        expect![[r#"
+            # `schemaFormat` is not supported. format: application/vnd.apache.avro;version=1.9.0, message: userSignedUp
+        "#]],
+    );
+}
+
+#[test]
+fn external_message() {
+    common::check_files(
+        common::Entry::RootFile,
+        r##"
+            ./tests/tmp/asyncapi.yaml
+            asyncapi: 2.6.0
+            info:
+              title: Account events
+              version: 1.0.0
+            channels:
+              user/signedup:
+                subscribe:
+                  message:
+                    $ref: "./messages/user_signed_up.yaml"
+              user/deleted:
+                subscribe:
+                  message:
+                    $ref: "./messages/user_deleted.yaml#/components/messages/UserDeleted"
+            ---
+            ./tests/tmp/messages/user_signed_up.yaml
+            payload:
+              type: object
+              properties:
+                email:
+                  type: string
+            ---
+            ./tests/tmp/messages/user_deleted.yaml
+            components:
+              messages:
+                UserDeleted:
+                  payload:
+                    type: object
+                    properties:
+                      id:
+                        type: string
+        "##,
+        Config::default(),
+        expect![[r#"
+            UserSignedUp = Dry::Schema::JSON do
+              optional(:email).value(:string)
+            end
+            UserDeleted = Dry::Schema::JSON do
+              optional(:id).value(:string)
+            end
+        "#]],
+    );
+}
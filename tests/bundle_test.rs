@@ -0,0 +1,256 @@
+use expect_test::expect;
+use openapi_dry_validation_generator::Config;
+
+mod common;
+
+#[test]
+fn bundle_external_references() {
+    common::check_files(
+        common::Entry::Bundle,
+        r##"
+            ./tests/tmp/bundle_root.yaml
+            openapi: 3.0.0
+            info:
+              title: Testing API overview
+              version: 1.0.0
+            paths:
+              /users:
+                get:
+                  operationId: list-users
+                  parameters:
+                    - $ref: ./parameters.yaml#/Limit
+                  responses:
+                    "200":
+                      description: OK
+                      content:
+                        application/json:
+                          schema:
+                            type: array
+                            items:
+                              $ref: ./schemas.yaml#/User
+                    default:
+                      $ref: "#/components/responses/Error"
+              /pets:
+                $ref: ./paths.yaml#/~1pets
+            components:
+              schemas:
+                User:
+                  type: string
+              responses:
+                Error:
+                  description: Error
+            ---
+            ./tests/tmp/parameters.yaml
+            Limit:
+              in: query
+              name: limit
+              schema:
+                type: integer
+            ---
+            ./tests/tmp/schemas.yaml
+            User:
+              type: object
+              properties:
+                pet:
+                  $ref: "#/Pet"
+                friends:
+                  type: array
+                  items:
+                    $ref: "#/User"
+            Pet:
+              type: object
+              properties:
+                name:
+                  type: string
+            ---
+            ./tests/tmp/paths.yaml
+            /pets:
+              get:
+                operationId: list-pets
+                responses:
+                  "200":
+                    description: OK
+                    content:
+                      application/json:
+                        schema:
+                          $ref: ./schemas.yaml#/Pet
+        "##,
+        Config::default(),
+        expect![[r#"
+            openapi: 3.0.0
+            info:
+              title: Testing API overview
+              version: 1.0.0
+            paths:
+              /users:
+                get:
+                  operationId: list-users
+                  parameters:
+                  - $ref: '#/components/parameters/Limit'
+                  responses:
+                    '200':
+                      description: OK
+                      content:
+                        application/json:
+                          schema:
+                            type: array
+                            items:
+                              $ref: '#/components/schemas/User_2'
+                    default:
+                      $ref: '#/components/responses/Error'
+              /pets:
+                get:
+                  operationId: list-pets
+                  responses:
+                    '200':
+                      description: OK
+                      content:
+                        application/json:
+                          schema:
+                            $ref: '#/components/schemas/Pet'
+            components:
+              schemas:
+                User:
+                  type: string
+                User_2:
+                  type: object
+                  properties:
+                    pet:
+                      $ref: '#/components/schemas/Pet'
+                    friends:
+                      type: array
+                      items:
+                        $ref: '#/components/schemas/User_2'
+                Pet:
+                  type: object
+                  properties:
+                    name:
+                      type: string
+              responses:
+                Error:
+                  description: Error
+              parameters:
+                Limit:
+                  in: query
+                  name: limit
+                  schema:
+                    type: integer
+        "#]],
+    );
+}
+
+#[test]
+fn bundle_reference_not_found() {
+    common::check_files(
+        common::Entry::Bundle,
+        r##"
+            ./tests/tmp/bundle_not_found.json
+            {
+                "openapi": "3.0.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/users": {
+                        "get": {
+                            "operationId": "list-users",
+                            "responses": {
+                                "200": {
+                                    "$ref": "./responses.json#/NotFound"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            ---
+            ./tests/tmp/responses.json
+            {
+                "OK": {
+                    "description": "OK"
+                }
+            }
+        "##,
+        Config::default(),
+        expect![[r#"
+            {
+              "openapi": "3.0.0",
+              "info": {
+                "title": "Testing API overview",
+                "version": "1.0.0"
+              },
+              "paths": {
+                "/users": {
+                  "get": {
+                    "operationId": "list-users",
+                    "responses": {
+                      "200": {
+                        "$ref": "./responses.json#/NotFound"
+                      }
+                    }
+                  }
+                }
+              }
+            }
+            # Failed to bundle reference. reference: ./responses.json#/NotFound, error: Not found.(pointer: /NotFound)
+        "#]],
+    );
+}
+
+#[test]
+fn bundle_properties_named_like_keywords() {
+    common::check_files(
+        common::Entry::Bundle,
+        r##"
+            ./tests/tmp/bundle_keyword_properties.yaml
+            openapi: 3.0.0
+            info:
+              title: Testing API overview
+              version: 1.0.0
+            paths: {}
+            components:
+              schemas:
+                Setting:
+                  type: object
+                  properties:
+                    default:
+                      $ref: ./values.yaml#/Value
+                    enum:
+                      type: array
+                      items:
+                        $ref: ./values.yaml#/Value
+                  default:
+                    default:
+                      $ref: ./not_a_reference.yaml
+            ---
+            ./tests/tmp/values.yaml
+            Value:
+              type: string
+        "##,
+        Config::default(),
+        expect![[r#"
+            openapi: 3.0.0
+            info:
+              title: Testing API overview
+              version: 1.0.0
+            paths: {}
+            components:
+              schemas:
+                Setting:
+                  type: object
+                  properties:
+                    default:
+                      $ref: '#/components/schemas/Value'
+                    enum:
+                      type: array
+                      items:
+                        $ref: '#/components/schemas/Value'
+                  default:
+                    default:
+                      $ref: ./not_a_reference.yaml
+                Value:
+                  type: string
+        "#]],
+    );
+}
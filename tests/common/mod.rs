@@ -1,8 +1,13 @@
-use std::fs::remove_file;
+use std::{collections::HashMap, sync::Arc};
 
 use expect_test::Expect;
 use httptest::{http::Uri, matchers::request, responders::status_code, Expectation, Server};
-use openapi_dry_validation_generator::generate_dry_validation_from_root_json;
+use openapi_dry_validation_generator::{
+    bundle_root_file_with_config, generate_dry_validation_from_json_schema_file_with_config,
+    generate_dry_validation_from_root_file_with_config, generate_dry_validation_from_root_json,
+    generate_dry_validation_from_root_json_with_config, Config, DocumentLoader, Generated,
+};
+use url::Url;
 
 #[allow(dead_code)]
 pub fn boilerplate(input: &str) -> String {
@@ -43,11 +48,23 @@ pub fn check(actual: &str, expect: Expect) {
 }
 
 #[allow(dead_code)]
-pub fn check_with_local_file(actual: &str, expect: Expect) {
-    let mut inputs = actual.split("---");
-    let openapi = inputs.next().unwrap();
-    let other_files = inputs
-        .map(|input_file| {
+pub fn check_with_config(actual: &str, config: &Config, expect: Expect) {
+    let openapi = generate_dry_validation_from_root_json_with_config(actual, config).code;
+    expect.assert_eq(&openapi);
+}
+
+/// Documents in memory instead of the file system, keyed by URL.
+#[derive(Debug, Default)]
+pub struct MemoryLoader {
+    documents: HashMap<Url, String>,
+}
+impl MemoryLoader {
+    /// Reads `---` separated files, each of which starts with the path of the file.
+    fn from_files(files: &str) -> (Self, Vec<Url>) {
+        let current_dir = Url::from_directory_path(std::env::current_dir().unwrap()).unwrap();
+        let mut loader = Self::default();
+        let mut urls = vec![];
+        for input_file in files.split("---").filter(|file| !file.trim().is_empty()) {
             let mut lines = input_file.lines();
             // ignore new line
             lines.next().unwrap();
@@ -57,17 +74,86 @@ pub fn check_with_local_file(actual: &str, expect: Expect) {
                 .trim();
             let file_content = lines.collect::<Vec<&str>>().join("\n");
 
-            (file_path, file_content)
-        })
-        .collect::<Vec<_>>();
-    for file in &other_files {
-        std::fs::write(file.0, &file.1).unwrap();
+            let url = current_dir.join(file_path).unwrap();
+            loader.documents.insert(url.clone(), file_content);
+            urls.push(url);
+        }
+
+        (loader, urls)
     }
+}
+impl DocumentLoader for MemoryLoader {
+    fn load(&self, url: &Url) -> anyhow::Result<String> {
+        match self.documents.get(url) {
+            Some(document) => Ok(document.clone()),
+            None => anyhow::bail!("Not found.(url: {url})"),
+        }
+    }
+}
 
-    let openapi = generate_dry_validation_from_root_json(openapi);
-    for file in &other_files {
-        remove_file(file.0).unwrap();
+/// How the inputs of `check_files` are read.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub enum Entry {
+    /// The first input is the root as JSON, and the rest are files that the root refers to.
+    Json,
+    /// The first file is the root, so that references are resolved against it.
+    RootFile,
+    /// The first file is a JSON Schema document.
+    JsonSchemaFile,
+    /// The first file is bundled instead of generated.
+    Bundle,
+}
+
+/// Generates from files in memory, which are separated by `---` and start with their paths.
+/// The diagnostics are checked as comments after the output,
+/// and the current directory in them is replaced with `{current_dir}`.
+#[allow(dead_code)]
+pub fn check_files(entry: Entry, actual: &str, config: Config, expect: Expect) {
+    let (root, files) = match entry {
+        Entry::Json => actual.split_once("---").unwrap_or((actual, "")),
+        _ => ("", actual),
+    };
+    let (loader, urls) = MemoryLoader::from_files(files);
+    let config = Config {
+        loader: Some(Arc::new(loader)),
+        ..config
+    };
+
+    let actual = match entry {
+        Entry::Json => with_diagnostics(generate_dry_validation_from_root_json_with_config(
+            root, &config,
+        )),
+        Entry::RootFile => with_diagnostics(generate_dry_validation_from_root_file_with_config(
+            urls[0].to_file_path().unwrap(),
+            &config,
+        )),
+        Entry::JsonSchemaFile => {
+            with_diagnostics(generate_dry_validation_from_json_schema_file_with_config(
+                urls[0].to_file_path().unwrap(),
+                &config,
+            ))
+        }
+        Entry::Bundle => {
+            let bundled = bundle_root_file_with_config(urls[0].to_file_path().unwrap(), &config);
+            let mut actual = bundled.document;
+            for diagnostic in bundled.diagnostics {
+                actual += &format!("# {diagnostic}\n");
+            }
+            actual
+        }
+    };
+    let current_dir = std::env::current_dir().unwrap();
+    expect.assert_eq(&actual.replace(current_dir.to_str().unwrap(), "{current_dir}"));
+}
+
+/// The code followed by the diagnostics as comments.
+#[allow(dead_code)]
+pub fn with_diagnostics(generated: Generated) -> String {
+    let mut actual = generated.code;
+    for diagnostic in generated.diagnostics {
+        actual += &format!("# {diagnostic}\n");
     }
 
-    expect.assert_eq(&openapi);
+    actual
 }
@@ -0,0 +1,588 @@
+mod common;
+
+use expect_test::{expect, Expect};
+use openapi_dry_validation_generator::{Config, ReadOnlyPolicy};
+
+fn openapi_with_components(schemas: &str, request_body_schema: &str) -> String {
+    format!(
+        r#"
+            {{
+                "openapi": "3.0.0",
+                "info": {{
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                }},
+                "paths": {{
+                    "/test/example": {{
+                        "post": {{
+                            "operationId": "testExample",
+                            "requestBody": {{
+                                "content": {{
+                                    "application/json": {{
+                                        "schema": {request_body_schema}
+                                    }}
+                                }}
+                            }},
+                            "responses": {{
+                                "200": {{
+                                    "description": "OK"
+                                }}
+                            }}
+                        }}
+                    }}
+                }},
+                "components": {{
+                    "schemas": {schemas}
+                }}
+            }}
+        "#
+    )
+}
+
+fn check_component_schema(schemas: &str, request_body_schema: &str, expect: Expect) {
+    common::check(
+        &openapi_with_components(schemas, request_body_schema),
+        expect,
+    );
+}
+
+const ORDER_SCHEMAS: &str = r##"
+    {
+        "Order": {
+            "type": "object",
+            "required": ["shipping"],
+            "properties": {
+                "shipping": {
+                    "$ref": "#/components/schemas/Address"
+                },
+                "billing": {
+                    "$ref": "#/components/schemas/Address"
+                },
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "$ref": "#/components/schemas/Item"
+                    }
+                }
+            }
+        },
+        "Address": {
+            "type": "object",
+            "required": ["city"],
+            "properties": {
+                "city": {
+                    "type": "string"
+                }
+            }
+        },
+        "Item": {
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string"
+                },
+                "quantity": {
+                    "$ref": "#/components/schemas/Quantity"
+                }
+            }
+        },
+        "Quantity": {
+            "type": "integer",
+            "minimum": 1
+        }
+    }
+"##;
+
+const ORDER_REQUEST_BODY: &str = r##"
+    {
+        "type": "object",
+        "properties": {
+            "order": {
+                "$ref": "#/components/schemas/Order"
+            },
+            "address": {
+                "$ref": "#/components/schemas/Address"
+            }
+        }
+    }
+"##;
+
+#[test]
+fn component_schemas_are_ordered_by_dependency() {
+    check_component_schema(
+        ORDER_SCHEMAS,
+        ORDER_REQUEST_BODY,
+        expect![[r#"
+            AddressSchema = Dry::Schema::JSON do
+              required(:city).value(:string)
+            end
+            ItemSchema = Dry::Schema::JSON do
+              optional(:name).value(:string)
+              optional(:quantity).value(:integer, min: 1)
+            end
+            OrderSchema = Dry::Schema::JSON do
+              required(:shipping).hash(AddressSchema)
+              optional(:billing).hash(AddressSchema)
+              optional(:items).value(:array).each(ItemSchema)
+            end
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:order).hash(OrderSchema)
+              optional(:address).hash(AddressSchema)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn unused_component_schema() {
+    check_component_schema(
+        r##"
+            {
+                "Unused": {
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string"
+                        }
+                    }
+                }
+            }
+        "##,
+        r##"
+            {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string"
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            UnusedSchema = Dry::Schema::JSON do
+              optional(:name).value(:string)
+            end
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:name).value(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn component_schema_at_root_of_body() {
+    check_component_schema(
+        ORDER_SCHEMAS,
+        r##"
+            {
+                "$ref": "#/components/schemas/Address"
+            }
+        "##,
+        expect![[r#"
+            AddressSchema = Dry::Schema::JSON do
+              required(:city).value(:string)
+            end
+            ItemSchema = Dry::Schema::JSON do
+              optional(:name).value(:string)
+              optional(:quantity).value(:integer, min: 1)
+            end
+            OrderSchema = Dry::Schema::JSON do
+              required(:shipping).hash(AddressSchema)
+              optional(:billing).hash(AddressSchema)
+              optional(:items).value(:array).each(ItemSchema)
+            end
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              required(:city).value(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn inline_component_schemas() {
+    common::check_with_config(
+        &openapi_with_components(ORDER_SCHEMAS, ORDER_REQUEST_BODY),
+        &Config {
+            inline_schemas: true,
+            ..Config::default()
+        },
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:order).value(:hash) do
+                required(:shipping).value(:hash) do
+                  required(:city).value(:string)
+                end
+                optional(:billing).value(:hash) do
+                  required(:city).value(:string)
+                end
+                optional(:items).value(:array).each(:hash?) do
+                  optional(:name).value(:string)
+                  optional(:quantity).value(:integer, min: 1)
+                end
+              end
+              optional(:address).value(:hash) do
+                required(:city).value(:string)
+              end
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn component_schema_with_rules() {
+    check_component_schema(
+        r##"
+            {
+                "Tags": {
+                    "type": "object",
+                    "properties": {
+                        "names": {
+                            "type": "array",
+                            "uniqueItems": true,
+                            "items": {
+                                "type": "string"
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        r##"
+            {
+                "type": "object",
+                "properties": {
+                    "tags": {
+                        "$ref": "#/components/schemas/Tags"
+                    },
+                    "history": {
+                        "type": "array",
+                        "items": {
+                            "$ref": "#/components/schemas/Tags"
+                        }
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            module Rules
+              # Yields each value at the path with its full path, where :* stands for every element of an array.
+              def self.each_value(value, path, value_path = [], &block)
+                return yield(value, value_path) if path.empty?
+
+                key, *rest = path
+                if key == :*
+                  return unless value.is_a?(Array)
+
+                  value.each_with_index { |item, index| each_value(item, rest, [*value_path, index], &block) }
+                elsif value.is_a?(Hash) && value.key?(key)
+                  each_value(value[key], rest, [*value_path, key], &block)
+                end
+              end
+
+              # Returns the index of the first element that already appeared earlier, or nil.
+              def self.first_duplicate_index(array)
+                seen = {}
+                array.each_with_index do |item, index|
+                  return index if seen.key?(item)
+
+                  seen[item] = true
+                end
+                nil
+              end
+
+              def self.unique_items(rule, path)
+                each_value(rule.values.data, path) do |value, value_path|
+                  next unless value.is_a?(Array)
+
+                  index = first_duplicate_index(value)
+                  rule.key(value_path).failure("must not contain duplicates, but the item at index #{index} is one") if index
+                end
+              end
+
+              # Validates each hash at the path with the schema, and adds its errors under the path.
+              def self.nested_schema(rule, path, schema)
+                each_value(rule.values.data, path) do |value, value_path|
+                  next unless value.is_a?(Hash)
+
+                  schema.call(value).errors.each { |error| rule.key([*value_path, *error.path]).failure(error.text) }
+                end
+              end
+            end
+
+            TagsSchema = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:names).value(:array).each(:str?)
+              end
+
+              rule do
+                Rules.unique_items(self, [:names])
+              end
+            end.new
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:tags).value(:hash)
+                optional(:history).value(:array).each(:hash?)
+              end
+
+              rule do
+                Rules.nested_schema(self, [:tags], TagsSchema)
+                Rules.nested_schema(self, [:history, :*], TagsSchema)
+              end
+            end.new
+        "#]],
+    );
+}
+
+#[test]
+fn component_schema_with_read_only_and_write_only() {
+    common::check_with_config(
+        r##"
+            {
+                "openapi": "3.0.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/test/example": {
+                        "post": {
+                            "operationId": "testExample",
+                            "requestBody": {
+                                "content": {
+                                    "application/json": {
+                                        "schema": {
+                                            "type": "object",
+                                            "properties": {
+                                                "account": {
+                                                    "$ref": "#/components/schemas/Account"
+                                                },
+                                                "address": {
+                                                    "$ref": "#/components/schemas/Address"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            },
+                            "responses": {
+                                "200": {
+                                    "description": "OK",
+                                    "content": {
+                                        "application/json": {
+                                            "schema": {
+                                                "type": "object",
+                                                "properties": {
+                                                    "user": {
+                                                        "$ref": "#/components/schemas/User"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "schemas": {
+                        "Account": {
+                            "type": "object",
+                            "properties": {
+                                "owner": {
+                                    "$ref": "#/components/schemas/User"
+                                }
+                            }
+                        },
+                        "Address": {
+                            "type": "object",
+                            "properties": {
+                                "city": {
+                                    "type": "string"
+                                }
+                            }
+                        },
+                        "User": {
+                            "type": "object",
+                            "required": ["id", "name"],
+                            "properties": {
+                                "id": {
+                                    "type": "integer",
+                                    "readOnly": true
+                                },
+                                "name": {
+                                    "type": "string"
+                                },
+                                "password": {
+                                    "type": "string",
+                                    "writeOnly": true
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        &Config {
+            read_only: ReadOnlyPolicy::Reject,
+            ..Config::default()
+        },
+        expect![[r#"
+            module Rules
+              # Yields each value at the path with its full path, where :* stands for every element of an array.
+              def self.each_value(value, path, value_path = [], &block)
+                return yield(value, value_path) if path.empty?
+
+                key, *rest = path
+                if key == :*
+                  return unless value.is_a?(Array)
+
+                  value.each_with_index { |item, index| each_value(item, rest, [*value_path, index], &block) }
+                elsif value.is_a?(Hash) && value.key?(key)
+                  each_value(value[key], rest, [*value_path, key], &block)
+                end
+              end
+
+              def self.forbidden(rule, path, message)
+                each_value(rule.values.data, path) { |_value, value_path| rule.key(value_path).failure(message) }
+              end
+
+              # Validates each hash at the path with the schema, and adds its errors under the path.
+              def self.nested_schema(rule, path, schema)
+                each_value(rule.values.data, path) do |value, value_path|
+                  next unless value.is_a?(Hash)
+
+                  schema.call(value).errors.each { |error| rule.key([*value_path, *error.path]).failure(error.text) }
+                end
+              end
+            end
+
+            UserSchema = Dry::Schema::JSON do
+              required(:id).value(:integer)
+              required(:name).value(:string)
+              optional(:password).value(:string)
+            end
+            UserRequestSchema = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:id).value(:integer)
+                required(:name).value(:string)
+                optional(:password).value(:string)
+              end
+
+              rule do
+                Rules.forbidden(self, [:id], "must not be sent, since it is read-only")
+              end
+            end.new
+            UserResponseSchema = Dry::Schema::JSON do
+              required(:id).value(:integer)
+              required(:name).value(:string)
+            end
+            AccountSchema = Dry::Schema::JSON do
+              optional(:owner).hash(UserSchema)
+            end
+            AccountRequestSchema = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:owner).value(:hash)
+              end
+
+              rule do
+                Rules.nested_schema(self, [:owner], UserRequestSchema)
+              end
+            end.new
+            AddressSchema = Dry::Schema::JSON do
+              optional(:city).value(:string)
+            end
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:account).value(:hash)
+                optional(:address).hash(AddressSchema)
+              end
+
+              rule do
+                Rules.nested_schema(self, [:account], AccountRequestSchema)
+              end
+            end.new
+            TestExampleResponse200 = Dry::Schema::JSON do
+              optional(:user).hash(UserResponseSchema)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn component_schema_with_defaults() {
+    check_component_schema(
+        r##"
+            {
+                "Address": {
+                    "type": "object",
+                    "properties": {
+                        "city": {
+                            "type": "string"
+                        },
+                        "country": {
+                            "type": "string",
+                            "default": "JP"
+                        }
+                    }
+                }
+            }
+        "##,
+        r##"
+            {
+                "type": "object",
+                "properties": {
+                    "address": {
+                        "$ref": "#/components/schemas/Address"
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            module Defaults
+              # Defaults of the keys of a hash, which are filled only if the hash is given or has a default of its own.
+              Nested = Struct.new(:defaults, :default)
+
+              # Fills the keys missing from the input with their defaults, e.g. `Defaults.apply(params, ShowUserDefaults)`.
+              def self.apply(input, defaults)
+                return input unless input.is_a?(Hash)
+
+                defaults.each_with_object(input.dup) do |(key, default), output|
+                  if !default.is_a?(Nested)
+                    output[key] = default unless output.key?(key)
+                  elsif output.key?(key) || !default.default.nil?
+                    output[key] = apply(output.fetch(key, default.default), default.defaults)
+                  end
+                end
+              end
+            end
+
+            AddressSchema = Dry::Schema::JSON do
+              optional(:city).value(:string)
+              optional(:country).value(:string)
+            end
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:address).hash(AddressSchema)
+            end
+            TestExampleRequestBodyDefaults = {
+              "address" => Defaults::Nested.new({
+                "country" => "JP"
+              }.freeze)
+            }.freeze
+        "#]],
+    );
+}
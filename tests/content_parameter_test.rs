@@ -0,0 +1,174 @@
+mod common;
+
+use expect_test::{expect, Expect};
+use openapi_dry_validation_generator::{
+    generate_dry_validation_from_root_json_with_config, Config,
+};
+
+fn check_parameters(parameters: &str, expect: Expect) {
+    let openapi = format!(
+        r#"
+            {{
+                "openapi": "3.0.0",
+                "info": {{
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                }},
+                "paths": {{
+                    "/users/{{id}}": {{
+                        "get": {{
+                            "operationId": "testExample",
+                            "parameters": {parameters},
+                            "responses": {{
+                                "200": {{
+                                    "description": "OK"
+                                }}
+                            }}
+                        }}
+                    }}
+                }}
+            }}
+        "#
+    );
+
+    let generated =
+        generate_dry_validation_from_root_json_with_config(&openapi, &Config::default());
+    expect.assert_eq(&common::with_diagnostics(generated));
+}
+
+#[test]
+fn json_content() {
+    check_parameters(
+        r#"
+            [
+                {
+                    "in": "query",
+                    "name": "filter",
+                    "required": true,
+                    "explode": false,
+                    "content": {
+                        "application/json": {
+                            "schema": {
+                                "type": "object",
+                                "required": ["name"],
+                                "properties": {
+                                    "name": {
+                                        "type": "string"
+                                    },
+                                    "ids": {
+                                        "type": "array",
+                                        "items": {
+                                            "type": "integer"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                {
+                    "in": "path",
+                    "name": "id",
+                    "required": true,
+                    "content": {
+                        "application/json": {
+                            "schema": {
+                                "type": "integer"
+                            }
+                        }
+                    }
+                },
+                {
+                    "in": "header",
+                    "name": "X-Context",
+                    "content": {
+                        "application/vnd.context+json": {
+                            "schema": {
+                                "type": "object"
+                            }
+                        }
+                    }
+                }
+            ]
+        "#,
+        expect![[r#"
+            module Coercions
+              # Parses the JSON text of the key, e.g. '{"name":"x"}' into { name: "x" }.
+              # An invalid text is left as it is, so that it fails validation.
+              def self.parse_json(input, name)
+                value = input[name]
+                return input unless value.is_a?(String)
+
+                input.merge(name => JSON.parse(value, symbolize_names: true))
+              rescue JSON::ParserError
+                input
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                input = Coercions.parse_json(input, :filter)
+                input
+              end
+              required(:filter).value(:hash) do
+                required(:name).value(:string)
+                optional(:ids).value(:array).each(:int?)
+              end
+            end
+            TestExamplePathParameters = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                input = Coercions.parse_json(input, :id)
+                input
+              end
+              required(:id).value(:integer)
+            end
+            TestExampleHeaders = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                input = Coercions.parse_json(input, :"X-Context")
+                input
+              end
+              optional(:"X-Context").value(:hash)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn non_json_content() {
+    check_parameters(
+        r#"
+            [
+                {
+                    "in": "query",
+                    "name": "filter",
+                    "content": {
+                        "text/plain": {
+                            "schema": {
+                                "type": "string"
+                            }
+                        }
+                    }
+                },
+                {
+                    "in": "path",
+                    "name": "id",
+                    "required": true,
+                    "schema": {
+                        "type": "integer"
+                    }
+                }
+            ]
+        "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExamplePathParameters = Dry::Schema::Params do
+              required(:id).value(:integer)
+            end
+            # Only JSON is supported in `content` of filter. media types: text/plain
+        "#]],
+    );
+}
@@ -0,0 +1,267 @@
+use expect_test::expect;
+use openapi_dry_validation_generator::Config;
+
+mod common;
+
+#[test]
+fn draft_07_definitions() {
+    common::check_files(
+        common::Entry::JsonSchemaFile,
+        r##"
+            ./tests/tmp/order.schema.json
+            {
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "title": "Order event",
+                "type": "object",
+                "required": ["id", "address"],
+                "properties": {
+                    "id": {
+                        "$ref": "#/definitions/Id"
+                    },
+                    "address": {
+                        "$ref": "#/definitions/Address"
+                    },
+                    "quantity": {
+                        "type": "integer",
+                        "exclusiveMinimum": 0
+                    }
+                },
+                "definitions": {
+                    "Id": {
+                        "type": "string",
+                        "maxLength": 36
+                    },
+                    "Address": {
+                        "type": "object",
+                        "properties": {
+                            "city": {
+                                "type": ["string", "null"]
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        Config::default(),
+        expect![[r#"
+            AddressSchema = Dry::Schema::JSON do
+              optional(:city).maybe(:string)
+            end
+            OrderEventSchema = Dry::Schema::JSON do
+              required(:id).value(:string, max_size: 36)
+              required(:address).hash(AddressSchema)
+              optional(:quantity).value(:integer, min: 1)
+            end
+            # Only `object` is supported at the root of Id
+        "#]],
+    );
+}
+
+#[test]
+fn defs_2020_12() {
+    common::check_files(
+        common::Entry::JsonSchemaFile,
+        r##"
+            ./tests/tmp/category.schema.json
+            {
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "const": "category"
+                    },
+                    "parent": {
+                        "$ref": "#"
+                    },
+                    "labels": {
+                        "$ref": "#/$defs/Labels"
+                    },
+                    "owner": {
+                        "$ref": "./user.schema.json"
+                    }
+                },
+                "$defs": {
+                    "Labels": {
+                        "type": "array",
+                        "items": {
+                            "type": "string"
+                        }
+                    }
+                }
+            }
+            ---
+            ./tests/tmp/user.schema.json
+            {
+                "type": "object",
+                "properties": {
+                    "email": {
+                        "type": "string"
+                    }
+                }
+            }
+        "##,
+        Config::default(),
+        expect![[r#"
+            module Rules
+              # Yields each value at the path with its full path, where :* stands for every element of an array.
+              def self.each_value(value, path, value_path = [], &block)
+                return yield(value, value_path) if path.empty?
+
+                key, *rest = path
+                if key == :*
+                  return unless value.is_a?(Array)
+
+                  value.each_with_index { |item, index| each_value(item, rest, [*value_path, index], &block) }
+                elsif value.is_a?(Hash) && value.key?(key)
+                  each_value(value[key], rest, [*value_path, key], &block)
+                end
+              end
+
+              # Validates each hash at the path with the schema, and adds its errors under the path.
+              def self.nested_schema(rule, path, schema)
+                each_value(rule.values.data, path) do |value, value_path|
+                  next unless value.is_a?(Hash)
+
+                  schema.call(value).errors.each { |error| rule.key([*value_path, *error.path]).failure(error.text) }
+                end
+              end
+            end
+
+            CategorySchema = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:name).value(:string, included_in: ["category"])
+                optional(:parent).value(:hash)
+                optional(:labels).value(:array).each(:str?)
+                optional(:owner).value(:hash) do
+                  optional(:email).value(:string)
+                end
+              end
+
+              rule do
+                Rules.nested_schema(self, [:parent], CategorySchema)
+              end
+            end.new
+            # Only `object` is supported at the root of Labels
+        "#]],
+    );
+}
+
+#[test]
+fn draft_07_tuple_items() {
+    common::check_files(
+        common::Entry::JsonSchemaFile,
+        r##"
+            ./tests/tmp/point.schema.json
+            {
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "title": "Point",
+                "type": "object",
+                "properties": {
+                    "coordinates": {
+                        "type": "array",
+                        "items": [
+                            { "type": "number" },
+                            { "type": "number" }
+                        ],
+                        "additionalItems": false
+                    },
+                    "label": {
+                        "type": "array",
+                        "items": [
+                            { "type": "string" },
+                            { "type": "integer" }
+                        ]
+                    }
+                }
+            }
+        "##,
+        Config::default(),
+        expect![[r#"
+            PointSchema = Dry::Schema::JSON do
+              optional(:coordinates).value(:array, max_size: 2).each(:float?)
+              optional(:label).value(:array)
+            end
+            # `items` is validated only as an array. pointer: #/properties/label
+        "#]],
+    );
+}
+
+#[test]
+fn inline_schemas_of_json_schema() {
+    common::check_files(
+        common::Entry::JsonSchemaFile,
+        r##"
+            ./tests/tmp/order.schema.json
+            {
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "title": "Order event",
+                "type": "object",
+                "properties": {
+                    "address": {
+                        "$ref": "#/definitions/Address"
+                    },
+                    "parent": {
+                        "$ref": "#"
+                    }
+                },
+                "definitions": {
+                    "Address": {
+                        "type": "object",
+                        "properties": {
+                            "city": {
+                                "type": "string"
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        Config {
+            inline_schemas: true,
+            ..Config::default()
+        },
+        expect![[r#"
+            module Rules
+              # Yields each value at the path with its full path, where :* stands for every element of an array.
+              def self.each_value(value, path, value_path = [], &block)
+                return yield(value, value_path) if path.empty?
+
+                key, *rest = path
+                if key == :*
+                  return unless value.is_a?(Array)
+
+                  value.each_with_index { |item, index| each_value(item, rest, [*value_path, index], &block) }
+                elsif value.is_a?(Hash) && value.key?(key)
+                  each_value(value[key], rest, [*value_path, key], &block)
+                end
+              end
+
+              # Validates each hash at the path with the schema, and adds its errors under the path.
+              def self.nested_schema(rule, path, schema)
+                each_value(rule.values.data, path) do |value, value_path|
+                  next unless value.is_a?(Hash)
+
+                  schema.call(value).errors.each { |error| rule.key([*value_path, *error.path]).failure(error.text) }
+                end
+              end
+            end
+
+            OrderEventSchema = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:address).value(:hash) do
+                  optional(:city).value(:string)
+                end
+                optional(:parent).value(:hash)
+              end
+
+              rule do
+                Rules.nested_schema(self, [:parent], OrderEventSchema)
+              end
+            end.new
+            AddressSchema = Dry::Schema::JSON do
+              optional(:city).value(:string)
+            end
+        "#]],
+    );
+}
@@ -0,0 +1,266 @@
+mod common;
+
+use expect_test::{expect, Expect};
+use openapi_dry_validation_generator::{
+    generate_dry_validation_from_root_json_with_config, Config,
+};
+
+fn check_request_body_schema(schema: &str, components: &str, expect: Expect) {
+    let openapi = format!(
+        r#"
+            {{
+                "openapi": "3.1.0",
+                "info": {{
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                }},
+                "paths": {{
+                    "/test/example": {{
+                        "post": {{
+                            "operationId": "testExample",
+                            "requestBody": {{
+                                "content": {{
+                                    "application/json": {{
+                                        "schema": {schema}
+                                    }}
+                                }}
+                            }},
+                            "responses": {{
+                                "200": {{
+                                    "description": "OK"
+                                }}
+                            }}
+                        }}
+                    }}
+                }},
+                "components": {{
+                    "schemas": {components}
+                }}
+            }}
+        "#
+    );
+
+    let generated =
+        generate_dry_validation_from_root_json_with_config(&openapi, &Config::default());
+    expect.assert_eq(&common::with_diagnostics(generated));
+}
+
+#[test]
+fn type_array_with_null() {
+    check_request_body_schema(
+        r#"
+            {
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "name": {
+                        "type": ["string", "null"],
+                        "maxLength": 10
+                    },
+                    "tags": {
+                        "type": ["array", "null"],
+                        "items": {
+                            "type": "string"
+                        }
+                    },
+                    "id": {
+                        "type": ["integer", "string"]
+                    }
+                }
+            }
+        "#,
+        "{}",
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              required(:name).maybe(:string, max_size: 10)
+              optional(:tags).maybe(:array).each(:str?)
+            end
+            # `AnyOf` is not supported in requestBody
+        "#]],
+    );
+}
+
+#[test]
+fn const_and_examples() {
+    check_request_body_schema(
+        r#"
+            {
+                "type": "object",
+                "properties": {
+                    "kind": {
+                        "type": "string",
+                        "const": "user",
+                        "examples": ["user"]
+                    },
+                    "level": {
+                        "type": "integer",
+                        "enum": [1, 2, 3, null],
+                        "exclusiveMinimum": 0,
+                        "exclusiveMaximum": 10
+                    }
+                }
+            }
+        "#,
+        "{}",
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:kind).value(:string, included_in: ["user"])
+              optional(:level).value(:integer, min: 1, max: 9, included_in: [1, 2, 3])
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn prefix_items() {
+    check_request_body_schema(
+        r#"
+            {
+                "type": "object",
+                "properties": {
+                    "point": {
+                        "type": "array",
+                        "prefixItems": [
+                            { "type": "number" },
+                            { "type": "number" }
+                        ],
+                        "items": false
+                    },
+                    "pair": {
+                        "type": "array",
+                        "prefixItems": [
+                            { "type": "string" },
+                            { "type": "integer" }
+                        ]
+                    }
+                }
+            }
+        "#,
+        "{}",
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:point).value(:array, max_size: 2).each(:float?)
+              optional(:pair).value(:array)
+            end
+            # `prefixItems` is validated only as an array. pointer: #/paths/~1test~1example/post/requestBody/content/application~1json/schema/properties/pair
+        "#]],
+    );
+}
+
+#[test]
+fn defs() {
+    check_request_body_schema(
+        r##"
+            {
+                "$ref": "#/components/schemas/Order"
+            }
+        "##,
+        r##"
+            {
+                "Order": {
+                    "type": "object",
+                    "properties": {
+                        "address": {
+                            "$ref": "#/components/schemas/Order/$defs/Address"
+                        }
+                    },
+                    "$defs": {
+                        "Address": {
+                            "type": "object",
+                            "properties": {
+                                "city": {
+                                    "type": ["string", "null"]
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            OrderSchema = Dry::Schema::JSON do
+              optional(:address).value(:hash) do
+                optional(:city).maybe(:string)
+              end
+            end
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:address).value(:hash) do
+                optional(:city).maybe(:string)
+              end
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn external_document() {
+    common::check_files(
+        common::Entry::Json,
+        r##"
+            {
+                "openapi": "3.1.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/test/example": {
+                        "post": {
+                            "operationId": "testExample",
+                            "requestBody": {
+                                "content": {
+                                    "application/json": {
+                                        "schema": {
+                                            "$ref": "./tests/tmp/openapi31_user.json#/User"
+                                        }
+                                    }
+                                }
+                            },
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            ---
+            ./tests/tmp/openapi31_user.json
+            {
+                "User": {
+                    "type": "object",
+                    "properties": {
+                        "nickname": {
+                            "type": ["string", "null"]
+                        },
+                        "pair": {
+                            "type": "array",
+                            "prefixItems": [
+                                { "type": "string" }
+                            ]
+                        }
+                    }
+                }
+            }
+        "##,
+        Config::default(),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:nickname).maybe(:string)
+              optional(:pair).value(:array)
+            end
+            # `prefixItems` is validated only as an array. pointer: file://{current_dir}/tests/tmp/openapi31_user.json#/User/properties/pair
+        "#]],
+    );
+}
@@ -108,19 +108,19 @@ fn operations() {
         "#,
         expect![[r#"
             GetTest = Dry::Schema::Params do
-              optional(:get_key).value(:string)
+              optional(:get_key).filled(:string)
             end
             PostTest = Dry::Schema::Params do
-              optional(:post_key).value(:integer)
+              optional(:post_key).filled(:integer)
             end
             PatchTest = Dry::Schema::Params do
-              optional(:patch_key).value(:boolean)
+              optional(:patch_key).filled(:boolean)
             end
             PutTest = Dry::Schema::Params do
-              optional(:put_key).value(:string)
+              optional(:put_key).filled(:string)
             end
             DeleteTest = Dry::Schema::Params do
-              optional(:delete_key).value(:integer)
+              optional(:delete_key).filled(:integer)
             end
         "#]],
     );
@@ -0,0 +1,178 @@
+mod common;
+
+use expect_test::{expect, Expect};
+use openapi_dry_validation_generator::{Config, DeprecatedPolicy};
+
+fn check_parameters(parameters: &str, config: &Config, expect: Expect) {
+    let openapi = common::boilerplate(&format!(
+        r#"
+            "/test/example": {{
+                "get": {{
+                    "operationId": "testExample",
+                    "parameters": {parameters},
+                    "responses": {{
+                        "200": {{
+                            "description": "OK"
+                        }}
+                    }}
+                }}
+            }}
+        "#
+    ));
+    common::check_with_config(&openapi, config, expect);
+}
+
+const DEPRECATED_PARAMETERS: &str = r#"
+    [
+        {
+            "in": "query",
+            "name": "name",
+            "schema": {
+                "type": "string"
+            }
+        },
+        {
+            "in": "query",
+            "name": "old_name",
+            "deprecated": true,
+            "schema": {
+                "type": "string"
+            }
+        }
+    ]
+"#;
+
+#[test]
+fn allow_empty_value() {
+    check_parameters(
+        r#"
+            [
+                {
+                    "in": "query",
+                    "name": "q",
+                    "required": true,
+                    "schema": {
+                        "type": "string"
+                    }
+                },
+                {
+                    "in": "query",
+                    "name": "cursor",
+                    "allowEmptyValue": true,
+                    "schema": {
+                        "type": "string"
+                    }
+                },
+                {
+                    "in": "query",
+                    "name": "parent",
+                    "schema": {
+                        "type": "integer",
+                        "nullable": true
+                    }
+                }
+            ]
+        "#,
+        &Config::default(),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              required(:q).filled(:string)
+              optional(:cursor).value(:string)
+              optional(:parent).maybe(:integer)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn deprecated_comment() {
+    check_parameters(
+        DEPRECATED_PARAMETERS,
+        &Config::default(),
+        expect![[r#"
+        TestExample = Dry::Schema::Params do
+          optional(:name).filled(:string)
+          # `old_name` is deprecated.
+          optional(:old_name).filled(:string)
+        end
+    "#]],
+    );
+}
+
+#[test]
+fn deprecated_log() {
+    check_parameters(
+        DEPRECATED_PARAMETERS,
+        &Config {
+            deprecated: DeprecatedPolicy::Log,
+            ..Default::default()
+        },
+        expect![[r##"
+            module Deprecations
+              class << self
+                # Called with the name of the schema and the deprecated keys which are present, e.g. to log them.
+                attr_accessor :handler
+              end
+              self.handler = ->(schema, keys) { warn("#{schema}: #{keys.join(", ")} deprecated") }
+
+              def self.report(schema, input, keys)
+                present = keys.select { |key| input.key?(key) }
+                handler.call(schema, present) unless present.empty?
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                Deprecations.report("TestExample", input, [:old_name])
+                input
+              end
+              optional(:name).filled(:string)
+              optional(:old_name).filled(:string)
+            end
+        "##]],
+    );
+}
+
+#[test]
+fn deprecated_reject() {
+    check_parameters(
+        DEPRECATED_PARAMETERS,
+        &Config {
+            deprecated: DeprecatedPolicy::Reject,
+            ..Default::default()
+        },
+        expect![[r#"
+            module Rules
+              # Yields each value at the path with its full path, where :* stands for every element of an array.
+              def self.each_value(value, path, value_path = [], &block)
+                return yield(value, value_path) if path.empty?
+
+                key, *rest = path
+                if key == :*
+                  return unless value.is_a?(Array)
+
+                  value.each_with_index { |item, index| each_value(item, rest, [*value_path, index], &block) }
+                elsif value.is_a?(Hash) && value.key?(key)
+                  each_value(value[key], rest, [*value_path, key], &block)
+                end
+              end
+
+              def self.forbidden(rule, path, message)
+                each_value(rule.values.data, path) { |_value, value_path| rule.key(value_path).failure(message) }
+              end
+            end
+
+            TestExample = Class.new(Dry::Validation::Contract) do
+              params do
+                optional(:name).filled(:string)
+                optional(:old_name).filled(:string)
+              end
+
+              rule do
+                Rules.forbidden(self, [:old_name], "must not be sent, since it is deprecated")
+              end
+            end.new
+        "#]],
+    );
+}
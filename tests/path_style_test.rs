@@ -0,0 +1,543 @@
+mod common;
+
+use expect_test::{expect, Expect};
+use openapi_dry_validation_generator::{
+    generate_dry_validation_from_root_json_with_config, Config,
+};
+
+fn check_parameters(path: &str, parameters: &str, expect: Expect) {
+    let openapi = format!(
+        r#"
+            {{
+                "openapi": "3.0.0",
+                "info": {{
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                }},
+                "paths": {{
+                    "{path}": {{
+                        "get": {{
+                            "operationId": "testExample",
+                            "parameters": {parameters},
+                            "responses": {{
+                                "200": {{
+                                    "description": "OK"
+                                }}
+                            }}
+                        }}
+                    }}
+                }}
+            }}
+        "#
+    );
+
+    let generated =
+        generate_dry_validation_from_root_json_with_config(&openapi, &Config::default());
+    expect.assert_eq(&common::with_diagnostics(generated));
+}
+
+/// The primitive, array and object of the style examples in the OpenAPI Specification,
+/// i.e. `5`, `[3, 4, 5]` and `{ "role": "admin", "firstName": "Alex" }`.
+fn check_path_style(style: &str, explode: bool, expect: Expect) {
+    check_parameters(
+        "/users/{id}/{ids}/{user}",
+        &format!(
+            r#"
+                [
+                    {{
+                        "in": "path",
+                        "name": "id",
+                        "required": true,
+                        "style": "{style}",
+                        "explode": {explode},
+                        "schema": {{
+                            "type": "integer"
+                        }}
+                    }},
+                    {{
+                        "in": "path",
+                        "name": "ids",
+                        "required": true,
+                        "style": "{style}",
+                        "explode": {explode},
+                        "schema": {{
+                            "type": "array",
+                            "items": {{
+                                "type": "integer"
+                            }}
+                        }}
+                    }},
+                    {{
+                        "in": "path",
+                        "name": "user",
+                        "required": true,
+                        "style": "{style}",
+                        "explode": {explode},
+                        "schema": {{
+                            "type": "object",
+                            "properties": {{
+                                "role": {{
+                                    "type": "string"
+                                }},
+                                "firstName": {{
+                                    "type": "string"
+                                }}
+                            }}
+                        }}
+                    }}
+                ]
+            "#
+        ),
+        expect,
+    );
+}
+
+#[test]
+fn simple() {
+    // 5, 3,4,5 and role,admin,firstName,Alex
+    check_path_style(
+        "simple",
+        false,
+        expect![[r#"
+        module Coercions
+          # Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+          def self.split(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+          end
+
+          # Splits the string of the key into a hash, e.g. "R,100,G,200" into { R: "100", G: "200" }.
+          # An odd number of elements is left as it is, so that it fails as a hash.
+          def self.split_pairs(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            pairs = value.delete_prefix(prefix).split(delimiter, -1)
+            return input if pairs.size.odd?
+
+            input.merge(name => pairs.each_slice(2).to_h { |key, item| [key.to_sym, item] })
+          end
+        end
+
+        TestExample = Dry::Schema::Params do
+        end
+        TestExamplePathParameters = Dry::Schema::Params do
+          before(:value_coercer) do |result|
+            input = result.to_h
+            input = Coercions.split(input, :ids, ",")
+            input = Coercions.split_pairs(input, :user, ",")
+            input
+          end
+          required(:id).value(:integer)
+          required(:ids).value(:array).each(:int?)
+          required(:user).value(:hash) do
+            optional(:role).value(:string)
+            optional(:firstName).value(:string)
+          end
+        end
+    "#]],
+    );
+}
+
+#[test]
+fn simple_exploded() {
+    // 5, 3,4,5 and role=admin,firstName=Alex
+    check_path_style(
+        "simple",
+        true,
+        expect![[r#"
+        module Coercions
+          # Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+          def self.split(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+          end
+
+          # Splits the string of the key into a hash, e.g. "R=100,G=200" into { R: "100", G: "200" }.
+          # A pair without "=" is left as it is, so that it fails as a hash.
+          def self.split_key_values(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            pairs = value.delete_prefix(prefix).split(delimiter, -1).map { |pair| pair.split("=", 2) }
+            return input unless pairs.all? { |pair| pair.size == 2 }
+
+            input.merge(name => pairs.to_h { |key, item| [key.to_sym, item] })
+          end
+        end
+
+        TestExample = Dry::Schema::Params do
+        end
+        TestExamplePathParameters = Dry::Schema::Params do
+          before(:value_coercer) do |result|
+            input = result.to_h
+            input = Coercions.split(input, :ids, ",")
+            input = Coercions.split_key_values(input, :user, ",")
+            input
+          end
+          required(:id).value(:integer)
+          required(:ids).value(:array).each(:int?)
+          required(:user).value(:hash) do
+            optional(:role).value(:string)
+            optional(:firstName).value(:string)
+          end
+        end
+    "#]],
+    );
+}
+
+#[test]
+fn label() {
+    // .5, .3,4,5 and .role,admin,firstName,Alex
+    check_path_style(
+        "label",
+        false,
+        expect![[r#"
+        module Coercions
+          # Removes the prefix of the string of the key, e.g. ";id=5" into "5".
+          def self.strip_prefix(input, name, prefix)
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            input.merge(name => value.delete_prefix(prefix))
+          end
+
+          # Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+          def self.split(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+          end
+
+          # Splits the string of the key into a hash, e.g. "R,100,G,200" into { R: "100", G: "200" }.
+          # An odd number of elements is left as it is, so that it fails as a hash.
+          def self.split_pairs(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            pairs = value.delete_prefix(prefix).split(delimiter, -1)
+            return input if pairs.size.odd?
+
+            input.merge(name => pairs.each_slice(2).to_h { |key, item| [key.to_sym, item] })
+          end
+        end
+
+        TestExample = Dry::Schema::Params do
+        end
+        TestExamplePathParameters = Dry::Schema::Params do
+          before(:value_coercer) do |result|
+            input = result.to_h
+            input = Coercions.strip_prefix(input, :id, ".")
+            input = Coercions.split(input, :ids, ",", prefix: ".")
+            input = Coercions.split_pairs(input, :user, ",", prefix: ".")
+            input
+          end
+          required(:id).value(:integer)
+          required(:ids).value(:array).each(:int?)
+          required(:user).value(:hash) do
+            optional(:role).value(:string)
+            optional(:firstName).value(:string)
+          end
+        end
+    "#]],
+    );
+}
+
+#[test]
+fn label_exploded() {
+    // .5, .3.4.5 and .role=admin.firstName=Alex
+    check_path_style(
+        "label",
+        true,
+        expect![[r#"
+        module Coercions
+          # Removes the prefix of the string of the key, e.g. ";id=5" into "5".
+          def self.strip_prefix(input, name, prefix)
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            input.merge(name => value.delete_prefix(prefix))
+          end
+
+          # Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+          def self.split(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+          end
+
+          # Splits the string of the key into a hash, e.g. "R=100,G=200" into { R: "100", G: "200" }.
+          # A pair without "=" is left as it is, so that it fails as a hash.
+          def self.split_key_values(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            pairs = value.delete_prefix(prefix).split(delimiter, -1).map { |pair| pair.split("=", 2) }
+            return input unless pairs.all? { |pair| pair.size == 2 }
+
+            input.merge(name => pairs.to_h { |key, item| [key.to_sym, item] })
+          end
+        end
+
+        TestExample = Dry::Schema::Params do
+        end
+        TestExamplePathParameters = Dry::Schema::Params do
+          before(:value_coercer) do |result|
+            input = result.to_h
+            input = Coercions.strip_prefix(input, :id, ".")
+            input = Coercions.split(input, :ids, ".", prefix: ".")
+            input = Coercions.split_key_values(input, :user, ".", prefix: ".")
+            input
+          end
+          required(:id).value(:integer)
+          required(:ids).value(:array).each(:int?)
+          required(:user).value(:hash) do
+            optional(:role).value(:string)
+            optional(:firstName).value(:string)
+          end
+        end
+    "#]],
+    );
+}
+
+#[test]
+fn matrix() {
+    // ;id=5, ;ids=3,4,5 and ;user=role,admin,firstName,Alex
+    check_path_style(
+        "matrix",
+        false,
+        expect![[r#"
+        module Coercions
+          # Removes the prefix of the string of the key, e.g. ";id=5" into "5".
+          def self.strip_prefix(input, name, prefix)
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            input.merge(name => value.delete_prefix(prefix))
+          end
+
+          # Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+          def self.split(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+          end
+
+          # Splits the string of the key into a hash, e.g. "R,100,G,200" into { R: "100", G: "200" }.
+          # An odd number of elements is left as it is, so that it fails as a hash.
+          def self.split_pairs(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            pairs = value.delete_prefix(prefix).split(delimiter, -1)
+            return input if pairs.size.odd?
+
+            input.merge(name => pairs.each_slice(2).to_h { |key, item| [key.to_sym, item] })
+          end
+        end
+
+        TestExample = Dry::Schema::Params do
+        end
+        TestExamplePathParameters = Dry::Schema::Params do
+          before(:value_coercer) do |result|
+            input = result.to_h
+            input = Coercions.strip_prefix(input, :id, ";id=")
+            input = Coercions.split(input, :ids, ",", prefix: ";ids=")
+            input = Coercions.split_pairs(input, :user, ",", prefix: ";user=")
+            input
+          end
+          required(:id).value(:integer)
+          required(:ids).value(:array).each(:int?)
+          required(:user).value(:hash) do
+            optional(:role).value(:string)
+            optional(:firstName).value(:string)
+          end
+        end
+    "#]],
+    );
+}
+
+#[test]
+fn matrix_exploded() {
+    // ;id=5, ;ids=3;ids=4;ids=5 and ;role=admin;firstName=Alex
+    check_path_style(
+        "matrix",
+        true,
+        expect![[r#"
+        module Coercions
+          # Removes the prefix of the string of the key, e.g. ";id=5" into "5".
+          def self.strip_prefix(input, name, prefix)
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            input.merge(name => value.delete_prefix(prefix))
+          end
+
+          # Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+          def self.split(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+          end
+
+          # Splits the string of the key into a hash, e.g. "R=100,G=200" into { R: "100", G: "200" }.
+          # A pair without "=" is left as it is, so that it fails as a hash.
+          def self.split_key_values(input, name, delimiter, prefix: "")
+            value = input[name]
+            return input unless value.is_a?(String) && value.start_with?(prefix)
+
+            pairs = value.delete_prefix(prefix).split(delimiter, -1).map { |pair| pair.split("=", 2) }
+            return input unless pairs.all? { |pair| pair.size == 2 }
+
+            input.merge(name => pairs.to_h { |key, item| [key.to_sym, item] })
+          end
+        end
+
+        TestExample = Dry::Schema::Params do
+        end
+        TestExamplePathParameters = Dry::Schema::Params do
+          before(:value_coercer) do |result|
+            input = result.to_h
+            input = Coercions.strip_prefix(input, :id, ";id=")
+            input = Coercions.split(input, :ids, ";ids=", prefix: ";ids=")
+            input = Coercions.split_key_values(input, :user, ";", prefix: ";")
+            input
+          end
+          required(:id).value(:integer)
+          required(:ids).value(:array).each(:int?)
+          required(:user).value(:hash) do
+            optional(:role).value(:string)
+            optional(:firstName).value(:string)
+          end
+        end
+    "#]],
+    );
+}
+
+#[test]
+fn path_parameter_not_in_path() {
+    check_parameters(
+        "/users",
+        r#"
+            [
+                {
+                    "in": "path",
+                    "name": "id",
+                    "required": true,
+                    "schema": {
+                        "type": "integer"
+                    }
+                }
+            ]
+        "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            # Path parameter is not found in the path. path: /users, name: id
+        "#]],
+    );
+}
+
+#[test]
+fn headers() {
+    check_parameters(
+        "/users",
+        r#"
+            [
+                {
+                    "in": "header",
+                    "name": "X-Request-ID",
+                    "required": true,
+                    "schema": {
+                        "type": "string"
+                    }
+                },
+                {
+                    "in": "header",
+                    "name": "X-Tags",
+                    "schema": {
+                        "type": "array",
+                        "items": {
+                            "type": "string"
+                        }
+                    }
+                },
+                {
+                    "in": "header",
+                    "name": "X-Color",
+                    "explode": true,
+                    "schema": {
+                        "type": "object",
+                        "properties": {
+                            "R": {
+                                "type": "integer"
+                            }
+                        }
+                    }
+                },
+                {
+                    "in": "header",
+                    "name": "Accept",
+                    "schema": {
+                        "type": "string"
+                    }
+                },
+                {
+                    "in": "cookie",
+                    "name": "session",
+                    "schema": {
+                        "type": "string"
+                    }
+                }
+            ]
+        "#,
+        expect![[r#"
+            module Coercions
+              # Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+              def self.split(input, name, delimiter, prefix: "")
+                value = input[name]
+                return input unless value.is_a?(String) && value.start_with?(prefix)
+
+                input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+              end
+
+              # Splits the string of the key into a hash, e.g. "R=100,G=200" into { R: "100", G: "200" }.
+              # A pair without "=" is left as it is, so that it fails as a hash.
+              def self.split_key_values(input, name, delimiter, prefix: "")
+                value = input[name]
+                return input unless value.is_a?(String) && value.start_with?(prefix)
+
+                pairs = value.delete_prefix(prefix).split(delimiter, -1).map { |pair| pair.split("=", 2) }
+                return input unless pairs.all? { |pair| pair.size == 2 }
+
+                input.merge(name => pairs.to_h { |key, item| [key.to_sym, item] })
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleHeaders = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                input = Coercions.split(input, :"X-Tags", ",")
+                input = Coercions.split_key_values(input, :"X-Color", ",")
+                input
+              end
+              required(:"X-Request-ID").value(:string)
+              optional(:"X-Tags").value(:array).each(:str?)
+              optional(:"X-Color").value(:hash) do
+                optional(:R).value(:integer)
+              end
+            end
+            # `in: cookie` is not supported in session
+        "#]],
+    );
+}
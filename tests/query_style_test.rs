@@ -0,0 +1,370 @@
+mod common;
+
+use expect_test::{expect, Expect};
+use openapi_dry_validation_generator::{
+    generate_dry_validation_from_root_json_with_config, Config,
+};
+
+fn check_parameters(parameters: &str, components: &str, expect: Expect) {
+    let openapi = format!(
+        r#"
+            {{
+                "openapi": "3.0.0",
+                "info": {{
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                }},
+                "paths": {{
+                    "/test/example": {{
+                        "get": {{
+                            "operationId": "testExample",
+                            "parameters": {parameters},
+                            "responses": {{
+                                "200": {{
+                                    "description": "OK"
+                                }}
+                            }}
+                        }}
+                    }}
+                }},
+                "components": {{
+                    "schemas": {components}
+                }}
+            }}
+        "#
+    );
+
+    let generated =
+        generate_dry_validation_from_root_json_with_config(&openapi, &Config::default());
+    expect.assert_eq(&common::with_diagnostics(generated));
+}
+
+#[test]
+fn form_not_exploded() {
+    check_parameters(
+        r#"
+            [
+                {
+                    "in": "query",
+                    "name": "ids",
+                    "required": true,
+                    "explode": false,
+                    "schema": {
+                        "type": "array",
+                        "items": {
+                            "type": "integer"
+                        }
+                    }
+                },
+                {
+                    "in": "query",
+                    "name": "color",
+                    "style": "form",
+                    "explode": false,
+                    "schema": {
+                        "type": "object",
+                        "properties": {
+                            "R": {
+                                "type": "integer"
+                            },
+                            "G": {
+                                "type": "integer"
+                            }
+                        }
+                    }
+                },
+                {
+                    "in": "query",
+                    "name": "name",
+                    "explode": false,
+                    "schema": {
+                        "type": "string"
+                    }
+                }
+            ]
+        "#,
+        "{}",
+        expect![[r#"
+            module Coercions
+              # Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+              def self.split(input, name, delimiter, prefix: "")
+                value = input[name]
+                return input unless value.is_a?(String) && value.start_with?(prefix)
+
+                input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+              end
+
+              # Splits the string of the key into a hash, e.g. "R,100,G,200" into { R: "100", G: "200" }.
+              # An odd number of elements is left as it is, so that it fails as a hash.
+              def self.split_pairs(input, name, delimiter, prefix: "")
+                value = input[name]
+                return input unless value.is_a?(String) && value.start_with?(prefix)
+
+                pairs = value.delete_prefix(prefix).split(delimiter, -1)
+                return input if pairs.size.odd?
+
+                input.merge(name => pairs.each_slice(2).to_h { |key, item| [key.to_sym, item] })
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                input = Coercions.split(input, :ids, ",")
+                input = Coercions.split_pairs(input, :color, ",")
+                input
+              end
+              required(:ids).filled(:array).each(:int?)
+              optional(:color).filled(:hash) do
+                optional(:R).value(:integer)
+                optional(:G).value(:integer)
+              end
+              optional(:name).filled(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn space_and_pipe_delimited() {
+    check_parameters(
+        r#"
+            [
+                {
+                    "in": "query",
+                    "name": "words",
+                    "style": "spaceDelimited",
+                    "schema": {
+                        "type": "array",
+                        "items": {
+                            "type": "string"
+                        }
+                    }
+                },
+                {
+                    "in": "query",
+                    "name": "ids",
+                    "style": "pipeDelimited",
+                    "explode": false,
+                    "schema": {
+                        "type": "array",
+                        "items": {
+                            "type": "integer"
+                        }
+                    }
+                }
+            ]
+        "#,
+        "{}",
+        expect![[r#"
+            module Coercions
+              # Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+              def self.split(input, name, delimiter, prefix: "")
+                value = input[name]
+                return input unless value.is_a?(String) && value.start_with?(prefix)
+
+                input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                input = Coercions.split(input, :words, " ")
+                input = Coercions.split(input, :ids, "|")
+                input
+              end
+              optional(:words).filled(:array).each(:str?)
+              optional(:ids).filled(:array).each(:int?)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn exploded_values_are_parsed_by_rack() {
+    check_parameters(
+        r#"
+            [
+                {
+                    "in": "query",
+                    "name": "ids",
+                    "style": "form",
+                    "explode": true,
+                    "schema": {
+                        "type": "array",
+                        "items": {
+                            "type": "integer"
+                        }
+                    }
+                },
+                {
+                    "in": "query",
+                    "name": "tags",
+                    "style": "pipeDelimited",
+                    "explode": true,
+                    "schema": {
+                        "type": "array",
+                        "items": {
+                            "type": "string"
+                        }
+                    }
+                },
+                {
+                    "in": "query",
+                    "name": "color",
+                    "style": "form",
+                    "explode": true,
+                    "schema": {
+                        "type": "object",
+                        "properties": {
+                            "R": {
+                                "type": "integer"
+                            },
+                            "G": {
+                                "type": "integer"
+                            }
+                        }
+                    }
+                },
+                {
+                    "in": "query",
+                    "name": "filter",
+                    "style": "deepObject",
+                    "explode": true,
+                    "schema": {
+                        "type": "object",
+                        "properties": {
+                            "name": {
+                                "type": "string"
+                            }
+                        }
+                    }
+                }
+            ]
+        "#,
+        "{}",
+        expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "color", ["R", "G"])
+                input
+              end
+              optional(:ids).filled(:array).each(:int?)
+              optional(:tags).filled(:array).each(:str?)
+              optional(:color).filled(:hash) do
+                optional(:R).value(:integer)
+                optional(:G).value(:integer)
+              end
+              optional(:filter).filled(:hash) do
+                optional(:name).value(:string)
+              end
+            end
+            # Rack keeps only the last of repeated keys, so an exploded array is read as `ids[]=1&ids[]=2` in ids
+            # Rack keeps only the last of repeated keys, so an exploded array is read as `tags[]=1&tags[]=2` in tags
+        "#]],
+    );
+}
+
+#[test]
+fn deep_object_only_for_objects() {
+    check_parameters(
+        r#"
+            [
+                {
+                    "in": "query",
+                    "name": "ids",
+                    "style": "deepObject",
+                    "schema": {
+                        "type": "array",
+                        "items": {
+                            "type": "integer"
+                        }
+                    }
+                }
+            ]
+        "#,
+        "{}",
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:ids).filled(:array).each(:int?)
+            end
+            # `deepObject` is only defined for objects in ids
+        "#]],
+    );
+}
+
+#[test]
+fn referenced_object_not_exploded() {
+    check_parameters(
+        r##"
+            [
+                {
+                    "in": "query",
+                    "name": "color",
+                    "explode": false,
+                    "schema": {
+                        "$ref": "#/components/schemas/Color"
+                    }
+                }
+            ]
+        "##,
+        r#"
+            {
+                "Color": {
+                    "type": "object",
+                    "required": ["R"],
+                    "properties": {
+                        "R": {
+                            "type": "integer"
+                        }
+                    }
+                }
+            }
+        "#,
+        expect![[r#"
+            module Coercions
+              # Splits the string of the key into a hash, e.g. "R,100,G,200" into { R: "100", G: "200" }.
+              # An odd number of elements is left as it is, so that it fails as a hash.
+              def self.split_pairs(input, name, delimiter, prefix: "")
+                value = input[name]
+                return input unless value.is_a?(String) && value.start_with?(prefix)
+
+                pairs = value.delete_prefix(prefix).split(delimiter, -1)
+                return input if pairs.size.odd?
+
+                input.merge(name => pairs.each_slice(2).to_h { |key, item| [key.to_sym, item] })
+              end
+            end
+
+            ColorSchema = Dry::Schema::JSON do
+              required(:R).value(:integer)
+            end
+            ColorParamsSchema = Dry::Schema::Params do
+              required(:R).value(:integer)
+            end
+            TestExample = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                input = Coercions.split_pairs(input, :color, ",")
+                input
+              end
+              optional(:color).filled(:hash, ColorParamsSchema)
+            end
+        "#]],
+    );
+}
@@ -2,7 +2,7 @@ mod common;
 
 use expect_test::{expect, Expect};
 
-use openapi_dry_validation_generator::generate_dry_validation_from_root_json;
+use openapi_dry_validation_generator::{generate_dry_validation_from_root_json, Config};
 
 fn check_parameters(actual: &str, expect: Expect) {
     let actual = common::boilerplate(&format!(
@@ -75,12 +75,12 @@ fn query_types() {
             "#,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:string_key).value(:string)
-              optional(:number_key).value(:float)
-              optional(:integer_key).value(:integer)
-              optional(:boolean_key).value(:boolean)
-              optional(:array_key).value(:array)
-              optional(:object_key).value(:hash)
+              optional(:string_key).filled(:string)
+              optional(:number_key).filled(:float)
+              optional(:integer_key).filled(:integer)
+              optional(:boolean_key).filled(:boolean)
+              optional(:array_key).filled(:array)
+              optional(:object_key).filled(:hash)
             end
         "#]],
     );
@@ -185,18 +185,18 @@ fn query_required_and_optional() {
             "#,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              required(:required_integer_key).value(:integer)
-              optional(:optional_integer_key).value(:integer)
-              required(:required_number_key).value(:float)
-              optional(:optional_number_key).value(:float)
-              required(:required_string_key).value(:string)
-              optional(:optional_string_key).value(:string)
-              required(:required_boolean_key).value(:boolean)
-              optional(:optional_boolean_key).value(:boolean)
-              required(:required_array_key).value(:array)
-              optional(:optional_array_key).value(:array)
-              required(:required_object_key).value(:hash)
-              optional(:optional_object_key).value(:hash)
+              required(:required_integer_key).filled(:integer)
+              optional(:optional_integer_key).filled(:integer)
+              required(:required_number_key).filled(:float)
+              optional(:optional_number_key).filled(:float)
+              required(:required_string_key).filled(:string)
+              optional(:optional_string_key).filled(:string)
+              required(:required_boolean_key).filled(:boolean)
+              optional(:optional_boolean_key).filled(:boolean)
+              required(:required_array_key).filled(:array)
+              optional(:optional_array_key).filled(:array)
+              required(:required_object_key).filled(:hash)
+              optional(:optional_object_key).filled(:hash)
             end
         "#]],
     );
@@ -252,10 +252,136 @@ fn query_validations() {
             "#,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              required(:integer_key).value(:integer, min: 1, max: 2)
-              required(:number_key).value(:float, min: 3, max: 4)
-              required(:string_key).value(:string, min_size: 5, max_size: 6)
-              required(:array_key).value(:array, min_size: 7, max_size: 8)
+              required(:integer_key).filled(:integer, min: 1, max: 2)
+              required(:number_key).filled(:float, min: 3, max: 4)
+              required(:string_key).filled(:string, min_size: 5, max_size: 6)
+              required(:array_key).filled(:array, min_size: 7, max_size: 8)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn query_integer_format_validations() {
+    check_parameters(
+        r#"
+                [
+                    {
+                        "in": "query",
+                        "name": "int32_key",
+                        "schema": {
+                            "type": "integer",
+                            "format": "int32"
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "narrowed_int32_key",
+                        "schema": {
+                            "type": "integer",
+                            "format": "int32",
+                            "minimum": 0,
+                            "maximum": 100
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "overflowed_int32_key",
+                        "schema": {
+                            "type": "integer",
+                            "format": "int32",
+                            "minimum": -9999999999,
+                            "maximum": 9999999999
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "exclusive_int32_key",
+                        "schema": {
+                            "type": "integer",
+                            "format": "int32",
+                            "minimum": 0,
+                            "exclusiveMinimum": true
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "int64_key",
+                        "schema": {
+                            "type": "integer",
+                            "format": "int64"
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "int32_item",
+                        "schema": {
+                            "type": "array",
+                            "items": {
+                                "type": "integer",
+                                "format": "int32"
+                            }
+                        }
+                    }
+                ]
+            "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:int32_key).filled(:integer, min: -2147483648, max: 2147483647)
+              optional(:narrowed_int32_key).filled(:integer, min: 0, max: 100)
+              optional(:overflowed_int32_key).filled(:integer, min: -2147483648, max: 2147483647)
+              optional(:exclusive_int32_key).filled(:integer, min: 1, max: 2147483647)
+              optional(:int64_key).filled(:integer)
+              optional(:int32_item).filled(:array).each(:int?, min: -2147483648, max: 2147483647)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn query_int64_bounds() {
+    common::check_with_config(
+        &common::boilerplate(
+            r#"
+                "/test/example": {
+                    "get": {
+                        "operationId": "testExample",
+                        "parameters": [
+                            {
+                                "in": "query",
+                                "name": "int64_key",
+                                "schema": {
+                                    "type": "integer",
+                                    "format": "int64"
+                                }
+                            },
+                            {
+                                "in": "query",
+                                "name": "narrowed_int64_key",
+                                "schema": {
+                                    "type": "integer",
+                                    "format": "int64",
+                                    "minimum": 0
+                                }
+                            }
+                        ],
+                        "responses": {
+                            "200": {
+                                "description": "OK"
+                            }
+                        }
+                    }
+                }
+            "#,
+        ),
+        &Config {
+            int64_bounds: true,
+            ..Config::default()
+        },
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:int64_key).filled(:integer, min: -9223372036854775808, max: 9223372036854775807)
+              optional(:narrowed_int64_key).filled(:integer, min: 0, max: 9223372036854775807)
             end
         "#]],
     );
@@ -300,10 +426,160 @@ fn query_float_validations() {
             "#,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              required(:number_key_1).value(:float, min: 1, max: 2)
-              required(:number_key_2).value(:float, min: 3, max: 4)
-              required(:number_key_3).value(:float, min: 5.1, max: 6.2)
+              required(:number_key_1).filled(:float, min: 1, max: 2)
+              required(:number_key_2).filled(:float, min: 3, max: 4)
+              required(:number_key_3).filled(:float, min: 5.1, max: 6.2)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn query_decimal_validations() {
+    check_parameters(
+        r#"
+                [
+                    {
+                        "in": "query",
+                        "name": "decimal_format_key",
+                        "required": true,
+                        "schema": {
+                            "type": "number",
+                            "format": "decimal",
+                            "minimum": 0.1,
+                            "maximum": 99999.99
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "decimal_ruby_type_key",
+                        "schema": {
+                            "type": "number",
+                            "x-ruby-type": "decimal",
+                            "minimum": 0,
+                            "exclusiveMinimum": true,
+                            "maximum": 1.5,
+                            "exclusiveMaximum": true
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "decimal_item",
+                        "schema": {
+                            "type": "array",
+                            "items": {
+                                "type": "number",
+                                "format": "decimal",
+                                "maximum": 0.3
+                            }
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "double_key",
+                        "schema": {
+                            "type": "number",
+                            "format": "double",
+                            "maximum": 0.3
+                        }
+                    }
+                ]
+            "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              required(:decimal_format_key).filled(:decimal, min: BigDecimal("0.1"), max: BigDecimal("99999.99"))
+              optional(:decimal_ruby_type_key).filled(:decimal, gt: BigDecimal("0"), lt: BigDecimal("1.5"))
+              optional(:decimal_item).filled(:array).each(:decimal?, max: BigDecimal("0.3"))
+              optional(:double_key).filled(:float, max: 0.3)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn query_decimal_bounds_beyond_f64() {
+    check_parameters(
+        r#"
+                [
+                    {
+                        "in": "query",
+                        "name": "decimal_key",
+                        "schema": {
+                            "type": "number",
+                            "format": "decimal",
+                            "minimum": 0.30000000000000001,
+                            "maximum": 99999999999999999.99
+                        }
+                    }
+                ]
+            "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:decimal_key).filled(:decimal, min: BigDecimal("0.30000000000000001"), max: BigDecimal("99999999999999999.99"))
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn query_decimal_bounds_only_from_bound_keywords() {
+    common::check_files(
+        common::Entry::Json,
+        &common::boilerplate(
+            r#"
+                "/test/example": {
+                    "get": {
+                        "operationId": "testExample",
+                        "parameters": [
+                            {
+                                "in": "query",
+                                "name": "decimal_key",
+                                "description": "Up to 99999999999999999.98",
+                                "schema": {
+                                    "type": "number",
+                                    "format": "decimal",
+                                    "maximum": 99999999999999999.99,
+                                    "example": 99999999999999999.97
+                                }
+                            },
+                            {
+                                "in": "query",
+                                "name": "rounded_key",
+                                "schema": {
+                                    "type": "number",
+                                    "format": "decimal",
+                                    "maximum": 0.30000000000000002
+                                }
+                            },
+                            {
+                                "in": "query",
+                                "name": "ambiguous_key",
+                                "schema": {
+                                    "type": "number",
+                                    "format": "decimal",
+                                    "minimum": 0.30000000000000001,
+                                    "maximum": 0.300000000000000015
+                                }
+                            }
+                        ],
+                        "responses": {
+                            "200": {
+                                "description": "OK"
+                            }
+                        }
+                    }
+                }
+            "#,
+        ),
+        Config::default(),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:decimal_key).filled(:decimal, max: BigDecimal("99999999999999999.99"))
+              optional(:rounded_key).filled(:decimal, max: BigDecimal("0.30000000000000002"))
+              optional(:ambiguous_key).filled(:decimal, min: BigDecimal("0.3"), max: BigDecimal("0.3"))
             end
+            # Different bounds round to the same number, so it can not be written as it is.(number: 0.3)
+            # Different bounds round to the same number, so it can not be written as it is.(number: 0.3)
         "#]],
     );
 }
@@ -377,12 +653,12 @@ fn query_item_types_in_array() {
             "#,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:integer_item).value(:array).each(:int?)
-              optional(:number_item).value(:array).each(:float?)
-              optional(:string_item).value(:array).each(:str?)
-              optional(:boolean_item).value(:array).each(:bool?)
-              optional(:array_item).value(:array).each(:array?)
-              optional(:object_item).value(:array).each(:hash?)
+              optional(:integer_item).filled(:array).each(:int?)
+              optional(:number_item).filled(:array).each(:float?)
+              optional(:string_item).filled(:array).each(:str?)
+              optional(:boolean_item).filled(:array).each(:bool?)
+              optional(:array_item).filled(:array).each(:array?)
+              optional(:object_item).filled(:array).each(:hash?)
             end
         "#]],
     );
@@ -477,12 +753,12 @@ fn query_item_types_with_validation_in_array() {
             "#,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:integer_item).value(:array, min_size: 1, max_size: 2).each(:int?, min: 3, max: 4)
-              optional(:number_item).value(:array, min_size: 5, max_size: 6).each(:float?, min: 7, max: 8)
-              optional(:string_item).value(:array, min_size: 9, max_size: 10).each(:str?, min_size: 11, max_size: 12)
-              optional(:boolean_item).value(:array, min_size: 13, max_size: 14).each(:bool?)
-              optional(:array_item).value(:array, min_size: 15, max_size: 16).each(:array?, min_size: 17, max_size: 18)
-              optional(:object_item).value(:array, min_size: 19, max_size: 20).each(:hash?)
+              optional(:integer_item).filled(:array, min_size: 1, max_size: 2).each(:int?, min: 3, max: 4)
+              optional(:number_item).filled(:array, min_size: 5, max_size: 6).each(:float?, min: 7, max: 8)
+              optional(:string_item).filled(:array, min_size: 9, max_size: 10).each(:str?, min_size: 11, max_size: 12)
+              optional(:boolean_item).filled(:array, min_size: 13, max_size: 14).each(:bool?)
+              optional(:array_item).filled(:array, min_size: 15, max_size: 16).each(:array?, min_size: 17, max_size: 18)
+              optional(:object_item).filled(:array, min_size: 19, max_size: 20).each(:hash?)
             end
         "#]],
     );
@@ -599,32 +875,32 @@ fn query_nested_array() {
             "#,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              required(:nested_integer).value(:array).each(:array?) do
+              required(:nested_integer).filled(:array).each(:array?) do
                 schema(:array?).each(:array?) do
                   schema(:array?).each(:int?)
                 end
               end
-              required(:nested_number).value(:array).each(:array?) do
+              required(:nested_number).filled(:array).each(:array?) do
                 schema(:array?).each(:array?) do
                   schema(:array?).each(:float?)
                 end
               end
-              required(:nested_string).value(:array).each(:array?) do
+              required(:nested_string).filled(:array).each(:array?) do
                 schema(:array?).each(:array?) do
                   schema(:array?).each(:str?)
                 end
               end
-              required(:nested_boolean).value(:array).each(:array?) do
+              required(:nested_boolean).filled(:array).each(:array?) do
                 schema(:array?).each(:array?) do
                   schema(:array?).each(:bool?)
                 end
               end
-              required(:nested_array).value(:array).each(:array?) do
+              required(:nested_array).filled(:array).each(:array?) do
                 schema(:array?).each(:array?) do
                   schema(:array?).each(:array?)
                 end
               end
-              required(:nested_object).value(:array).each(:array?) do
+              required(:nested_object).filled(:array).each(:array?) do
                 schema(:array?).each(:array?) do
                   schema(:array?).each(:hash?)
                 end
@@ -789,32 +1065,32 @@ fn query_nested_array_with_validation() {
             "#,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              required(:nested_integer).value(:array, min_size: 1, max_size: 2).each(:array?, min_size: 3, max_size: 4) do
+              required(:nested_integer).filled(:array, min_size: 1, max_size: 2).each(:array?, min_size: 3, max_size: 4) do
                 schema(:array?).each(:array?, min_size: 5, max_size: 6) do
                   schema(:array?).each(:int?, min: 7, max: 8)
                 end
               end
-              required(:nested_number).value(:array, min_size: 9, max_size: 10).each(:array?, min_size: 11, max_size: 12) do
+              required(:nested_number).filled(:array, min_size: 9, max_size: 10).each(:array?, min_size: 11, max_size: 12) do
                 schema(:array?).each(:array?, min_size: 13, max_size: 14) do
                   schema(:array?).each(:float?, min: 15, max: 16)
                 end
               end
-              required(:nested_string).value(:array, min_size: 17, max_size: 18).each(:array?, min_size: 19, max_size: 20) do
+              required(:nested_string).filled(:array, min_size: 17, max_size: 18).each(:array?, min_size: 19, max_size: 20) do
                 schema(:array?).each(:array?, min_size: 21, max_size: 22) do
                   schema(:array?).each(:str?, min_size: 23, max_size: 24)
                 end
               end
-              required(:nested_boolean).value(:array, min_size: 25, max_size: 26).each(:array?, min_size: 27, max_size: 28) do
+              required(:nested_boolean).filled(:array, min_size: 25, max_size: 26).each(:array?, min_size: 27, max_size: 28) do
                 schema(:array?).each(:array?, min_size: 29, max_size: 30) do
                   schema(:array?).each(:bool?)
                 end
               end
-              required(:nested_array).value(:array, min_size: 31, max_size: 32).each(:array?, min_size: 33, max_size: 34) do
+              required(:nested_array).filled(:array, min_size: 31, max_size: 32).each(:array?, min_size: 33, max_size: 34) do
                 schema(:array?).each(:array?, min_size: 35, max_size: 36) do
                   schema(:array?).each(:array?, min_size: 37, max_size: 38)
                 end
               end
-              required(:nested_object).value(:array, min_size: 39, max_size: 40).each(:array?, min_size: 41, max_size: 42) do
+              required(:nested_object).filled(:array, min_size: 39, max_size: 40).each(:array?, min_size: 41, max_size: 42) do
                 schema(:array?).each(:array?, min_size: 43, max_size: 44) do
                   schema(:array?).each(:hash?)
                 end
@@ -859,8 +1135,26 @@ fn query_property_types_in_hash() {
                 ]
             "#,
         expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
             TestExample = Dry::Schema::Params do
-              optional(:integer_property).value(:hash) do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "integer_property", ["integer_prop", "number_prop", "string_prop", "boolean_prop", "array_prop", "object_prop"])
+                input
+              end
+              optional(:integer_property).filled(:hash) do
                 optional(:integer_prop).value(:integer)
                 optional(:number_prop).value(:float)
                 optional(:string_prop).value(:string)
@@ -916,8 +1210,26 @@ fn query_property_types_with_validation_in_object() {
                 ]
             "#,
         expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
             TestExample = Dry::Schema::Params do
-              optional(:property_types).value(:hash) do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "property_types", ["integer_prop", "number_prop", "string_prop", "boolean_prop", "array_prop", "object_prop"])
+                input
+              end
+              optional(:property_types).filled(:hash) do
                 optional(:integer_prop).value(:integer, min: 1, max: 2)
                 optional(:number_prop).value(:float, min: 3, max: 4)
                 optional(:string_prop).value(:string, min_size: 5, max_size: 6)
@@ -976,8 +1288,26 @@ fn query_nested_object() {
                 ]
             "#,
         expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
             TestExample = Dry::Schema::Params do
-              required(:nested_object).value(:hash) do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "nested_object", ["nested_1"])
+                input
+              end
+              required(:nested_object).filled(:hash) do
                 optional(:nested_1).value(:hash) do
                   optional(:nested_2).value(:hash) do
                     optional(:nested_3_1).value(:integer)
@@ -1048,8 +1378,26 @@ fn query_nested_object_with_validation() {
                 ]
             "#,
         expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
             TestExample = Dry::Schema::Params do
-              required(:nested_object).value(:hash) do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "nested_object", ["nested_1"])
+                input
+              end
+              required(:nested_object).filled(:hash) do
                 optional(:nested_1).value(:hash) do
                   optional(:nested_2).value(:hash) do
                     optional(:nested_3_1).value(:integer, min: 1, max: 2)
@@ -1110,18 +1458,36 @@ fn query_nested_object_with_required() {
                 ]
             "#,
         expect![[r#"
-                TestExample = Dry::Schema::Params do
-                  required(:nested_object).value(:hash) do
-                    required(:nested_1).value(:hash) do
-                      required(:nested_2).value(:hash) do
-                        required(:nested_3_1).value(:integer, min: 1, max: 2)
-                        optional(:nested_3_2).value(:string, min_size: 3, max_size: 4)
-                        required(:nested_3_3).value(:boolean)
-                      end
-                    end
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "nested_object", ["nested_1"])
+                input
+              end
+              required(:nested_object).filled(:hash) do
+                required(:nested_1).value(:hash) do
+                  required(:nested_2).value(:hash) do
+                    required(:nested_3_1).value(:integer, min: 1, max: 2)
+                    optional(:nested_3_2).value(:string, min_size: 3, max_size: 4)
+                    required(:nested_3_3).value(:boolean)
                   end
                 end
-            "#]],
+              end
+            end
+        "#]],
     );
 }
 
@@ -1152,13 +1518,363 @@ fn query_object_required_with_the_same_name() {
                 ]
             "#,
         expect![[r#"
-                TestExample = Dry::Schema::Params do
-                  required(:nested_object).value(:hash) do
-                    required(:same_key).value(:hash) do
-                      optional(:same_key).value(:boolean)
-                    end
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "nested_object", ["same_key"])
+                input
+              end
+              required(:nested_object).filled(:hash) do
+                required(:same_key).value(:hash) do
+                  optional(:same_key).value(:boolean)
+                end
+              end
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn query_unique_items() {
+    check_parameters(
+        r#"
+                [
+                    {
+                        "in": "query",
+                        "name": "unique_key",
+                        "schema": {
+                            "type": "array",
+                            "uniqueItems": true,
+                            "minItems": 1,
+                            "items": {
+                                "type": "integer"
+                            }
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "non_unique_key",
+                        "schema": {
+                            "type": "array",
+                            "uniqueItems": false
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "nested_unique_key",
+                        "schema": {
+                            "type": "array",
+                            "items": {
+                                "type": "array",
+                                "uniqueItems": true,
+                                "items": {
+                                    "type": "array",
+                                    "uniqueItems": true
+                                }
+                            }
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "object_key",
+                        "schema": {
+                            "type": "object",
+                            "properties": {
+                                "unique_prop": {
+                                    "type": "array",
+                                    "uniqueItems": true
+                                }
+                            }
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "object_item_key",
+                        "schema": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "unique_prop": {
+                                        "type": "array",
+                                        "uniqueItems": true
+                                    }
+                                }
+                            }
+                        }
+                    }
+                ]
+            "#,
+        expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
+            module Rules
+              # Yields each value at the path with its full path, where :* stands for every element of an array.
+              def self.each_value(value, path, value_path = [], &block)
+                return yield(value, value_path) if path.empty?
+
+                key, *rest = path
+                if key == :*
+                  return unless value.is_a?(Array)
+
+                  value.each_with_index { |item, index| each_value(item, rest, [*value_path, index], &block) }
+                elsif value.is_a?(Hash) && value.key?(key)
+                  each_value(value[key], rest, [*value_path, key], &block)
+                end
+              end
+
+              # Returns the index of the first element that already appeared earlier, or nil.
+              def self.first_duplicate_index(array)
+                seen = {}
+                array.each_with_index do |item, index|
+                  return index if seen.key?(item)
+
+                  seen[item] = true
+                end
+                nil
+              end
+
+              def self.unique_items(rule, path)
+                each_value(rule.values.data, path) do |value, value_path|
+                  next unless value.is_a?(Array)
+
+                  index = first_duplicate_index(value)
+                  rule.key(value_path).failure("must not contain duplicates, but the item at index #{index} is one") if index
+                end
+              end
+            end
+
+            TestExample = Class.new(Dry::Validation::Contract) do
+              params do
+                before(:key_coercer) do |result|
+                  input = result.to_h
+                  input = Coercions.gather_keys(input, "object_key", ["unique_prop"])
+                  input
+                end
+                optional(:unique_key).filled(:array, min_size: 1).each(:int?)
+                optional(:non_unique_key).filled(:array)
+                optional(:nested_unique_key).filled(:array).each(:array?) do
+                  schema(:array?).each(:array?)
+                end
+                optional(:object_key).filled(:hash) do
+                  optional(:unique_prop).value(:array)
+                end
+                optional(:object_item_key).filled(:array).each(:hash?) do
+                  optional(:unique_prop).value(:array)
+                end
+              end
+
+              rule do
+                Rules.unique_items(self, [:unique_key])
+                Rules.unique_items(self, [:nested_unique_key, :*])
+                Rules.unique_items(self, [:nested_unique_key, :*, :*])
+                Rules.unique_items(self, [:object_key, :unique_prop])
+                Rules.unique_items(self, [:object_item_key, :*, :unique_prop])
+              end
+            end.new
+        "#]],
+    );
+}
+
+#[test]
+fn query_defaults() {
+    check_parameters(
+        r#"
+                [
+                    {
+                        "in": "query",
+                        "name": "limit",
+                        "schema": {
+                            "type": "integer",
+                            "default": 20
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "no_default",
+                        "schema": {
+                            "type": "string"
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "tags",
+                        "schema": {
+                            "type": "array",
+                            "items": {
+                                "type": "string"
+                            },
+                            "default": ["a", "b#{c}"]
+                        }
+                    },
+                    {
+                        "in": "query",
+                        "name": "filter",
+                        "schema": {
+                            "type": "object",
+                            "properties": {
+                                "sort": {
+                                    "type": "string",
+                                    "default": "asc"
+                                },
+                                "deleted": {
+                                    "type": "boolean",
+                                    "default": false
+                                },
+                                "page": {
+                                    "type": "object",
+                                    "properties": {
+                                        "size": {
+                                            "type": "integer",
+                                            "default": 10
+                                        }
+                                    }
+                                }
+                            },
+                            "default": {
+                                "deleted": true
+                            }
+                        }
+                    }
+                ]
+            "#,
+        expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
+            module Defaults
+              # Defaults of the keys of a hash, which are filled only if the hash is given or has a default of its own.
+              Nested = Struct.new(:defaults, :default)
+
+              # Fills the keys missing from the input with their defaults, e.g. `Defaults.apply(params, ShowUserDefaults)`.
+              def self.apply(input, defaults)
+                return input unless input.is_a?(Hash)
+
+                defaults.each_with_object(input.dup) do |(key, default), output|
+                  if !default.is_a?(Nested)
+                    output[key] = default unless output.key?(key)
+                  elsif output.key?(key) || !default.default.nil?
+                    output[key] = apply(output.fetch(key, default.default), default.defaults)
                   end
                 end
-            "#]],
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "filter", ["sort", "deleted", "page"])
+                input
+              end
+              optional(:limit).filled(:integer)
+              optional(:no_default).filled(:string)
+              optional(:tags).filled(:array).each(:str?)
+              optional(:filter).filled(:hash) do
+                optional(:sort).value(:string)
+                optional(:deleted).value(:boolean)
+                optional(:page).value(:hash) do
+                  optional(:size).value(:integer)
+                end
+              end
+            end
+            TestExampleDefaults = {
+              "limit" => 20,
+              "tags" => ["a", "b\#{c}"].freeze,
+              "filter" => Defaults::Nested.new({
+                "sort" => "asc",
+                "deleted" => false,
+                "page" => Defaults::Nested.new({
+                  "size" => 10
+                }.freeze)
+              }.freeze, {
+                "deleted" => true
+              }.freeze)
+            }.freeze
+        "#]],
+    );
+}
+
+#[test]
+fn query_ignores_read_only_property() {
+    check_parameters(
+        r#"
+                [
+                    {
+                        "in": "query",
+                        "name": "filter",
+                        "schema": {
+                            "type": "object",
+                            "properties": {
+                                "id": {
+                                    "type": "integer",
+                                    "readOnly": true
+                                },
+                                "name": {
+                                    "type": "string"
+                                }
+                            }
+                        }
+                    }
+                ]
+            "#,
+        expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "filter", ["id", "name"])
+                input
+              end
+              optional(:filter).filled(:hash) do
+                optional(:name).value(:string)
+              end
+            end
+        "#]],
     );
 }
@@ -0,0 +1,341 @@
+mod common;
+
+use expect_test::{expect, Expect};
+
+fn check_recursive_schema(schemas: &str, request_body_schema: &str, expect: Expect) {
+    let actual = format!(
+        r#"
+            {{
+                "openapi": "3.0.0",
+                "info": {{
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                }},
+                "paths": {{
+                    "/test/example": {{
+                        "post": {{
+                            "operationId": "testExample",
+                            "requestBody": {{
+                                "content": {{
+                                    "application/json": {{
+                                        "schema": {request_body_schema}
+                                    }}
+                                }}
+                            }},
+                            "responses": {{
+                                "200": {{
+                                    "description": "OK"
+                                }}
+                            }}
+                        }}
+                    }}
+                }},
+                "components": {{
+                    "schemas": {schemas}
+                }}
+            }}
+        "#
+    );
+    common::check(&actual, expect);
+}
+
+#[test]
+fn direct_recursion() {
+    check_recursive_schema(
+        r##"
+            {
+                "TreeNode": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": {
+                            "type": "string"
+                        },
+                        "parent": {
+                            "$ref": "#/components/schemas/TreeNode"
+                        },
+                        "children": {
+                            "type": "array",
+                            "items": {
+                                "$ref": "#/components/schemas/TreeNode"
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        r##"
+            {
+                "type": "object",
+                "properties": {
+                    "root": {
+                        "$ref": "#/components/schemas/TreeNode"
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            module Rules
+              # Yields each value at the path with its full path, where :* stands for every element of an array.
+              def self.each_value(value, path, value_path = [], &block)
+                return yield(value, value_path) if path.empty?
+
+                key, *rest = path
+                if key == :*
+                  return unless value.is_a?(Array)
+
+                  value.each_with_index { |item, index| each_value(item, rest, [*value_path, index], &block) }
+                elsif value.is_a?(Hash) && value.key?(key)
+                  each_value(value[key], rest, [*value_path, key], &block)
+                end
+              end
+
+              # Validates each hash at the path with the schema, and adds its errors under the path.
+              def self.nested_schema(rule, path, schema)
+                each_value(rule.values.data, path) do |value, value_path|
+                  next unless value.is_a?(Hash)
+
+                  schema.call(value).errors.each { |error| rule.key([*value_path, *error.path]).failure(error.text) }
+                end
+              end
+            end
+
+            TreeNodeSchema = Class.new(Dry::Validation::Contract) do
+              json do
+                required(:name).value(:string)
+                optional(:parent).value(:hash)
+                optional(:children).value(:array).each(:hash?)
+              end
+
+              rule do
+                Rules.nested_schema(self, [:parent], TreeNodeSchema)
+                Rules.nested_schema(self, [:children, :*], TreeNodeSchema)
+              end
+            end.new
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:root).value(:hash)
+              end
+
+              rule do
+                Rules.nested_schema(self, [:root], TreeNodeSchema)
+              end
+            end.new
+        "#]],
+    );
+}
+
+#[test]
+fn direct_recursion_at_root_of_body() {
+    check_recursive_schema(
+        r##"
+            {
+                "TreeNode": {
+                    "type": "object",
+                    "properties": {
+                        "children": {
+                            "type": "array",
+                            "items": {
+                                "$ref": "#/components/schemas/TreeNode"
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        r##"
+            {
+                "$ref": "#/components/schemas/TreeNode"
+            }
+        "##,
+        expect![[r#"
+            module Rules
+              # Yields each value at the path with its full path, where :* stands for every element of an array.
+              def self.each_value(value, path, value_path = [], &block)
+                return yield(value, value_path) if path.empty?
+
+                key, *rest = path
+                if key == :*
+                  return unless value.is_a?(Array)
+
+                  value.each_with_index { |item, index| each_value(item, rest, [*value_path, index], &block) }
+                elsif value.is_a?(Hash) && value.key?(key)
+                  each_value(value[key], rest, [*value_path, key], &block)
+                end
+              end
+
+              # Validates each hash at the path with the schema, and adds its errors under the path.
+              def self.nested_schema(rule, path, schema)
+                each_value(rule.values.data, path) do |value, value_path|
+                  next unless value.is_a?(Hash)
+
+                  schema.call(value).errors.each { |error| rule.key([*value_path, *error.path]).failure(error.text) }
+                end
+              end
+            end
+
+            TreeNodeSchema = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:children).value(:array).each(:hash?)
+              end
+
+              rule do
+                Rules.nested_schema(self, [:children, :*], TreeNodeSchema)
+              end
+            end.new
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:children).value(:array).each(:hash?)
+              end
+
+              rule do
+                Rules.nested_schema(self, [:children, :*], TreeNodeSchema)
+              end
+            end.new
+        "#]],
+    );
+}
+
+#[test]
+fn mutual_recursion() {
+    check_recursive_schema(
+        r##"
+            {
+                "Person": {
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string"
+                        },
+                        "employer": {
+                            "$ref": "#/components/schemas/Company"
+                        }
+                    }
+                },
+                "Company": {
+                    "type": "object",
+                    "properties": {
+                        "employees": {
+                            "type": "array",
+                            "items": {
+                                "$ref": "#/components/schemas/Person"
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        r##"
+            {
+                "type": "object",
+                "properties": {
+                    "person": {
+                        "$ref": "#/components/schemas/Person"
+                    },
+                    "company": {
+                        "$ref": "#/components/schemas/Company"
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            module Rules
+              # Yields each value at the path with its full path, where :* stands for every element of an array.
+              def self.each_value(value, path, value_path = [], &block)
+                return yield(value, value_path) if path.empty?
+
+                key, *rest = path
+                if key == :*
+                  return unless value.is_a?(Array)
+
+                  value.each_with_index { |item, index| each_value(item, rest, [*value_path, index], &block) }
+                elsif value.is_a?(Hash) && value.key?(key)
+                  each_value(value[key], rest, [*value_path, key], &block)
+                end
+              end
+
+              # Validates each hash at the path with the schema, and adds its errors under the path.
+              def self.nested_schema(rule, path, schema)
+                each_value(rule.values.data, path) do |value, value_path|
+                  next unless value.is_a?(Hash)
+
+                  schema.call(value).errors.each { |error| rule.key([*value_path, *error.path]).failure(error.text) }
+                end
+              end
+            end
+
+            CompanySchema = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:employees).value(:array).each(:hash?)
+              end
+
+              rule do
+                Rules.nested_schema(self, [:employees, :*], PersonSchema)
+              end
+            end.new
+            PersonSchema = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:name).value(:string)
+                optional(:employer).value(:hash)
+              end
+
+              rule do
+                Rules.nested_schema(self, [:employer], CompanySchema)
+              end
+            end.new
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:person).value(:hash)
+                optional(:company).value(:hash)
+              end
+
+              rule do
+                Rules.nested_schema(self, [:person], PersonSchema)
+                Rules.nested_schema(self, [:company], CompanySchema)
+              end
+            end.new
+        "#]],
+    );
+}
+
+#[test]
+fn circular_reference_alias_is_skipped() {
+    check_recursive_schema(
+        r##"
+            {
+                "Alias1": {
+                    "$ref": "#/components/schemas/Alias2"
+                },
+                "Alias2": {
+                    "$ref": "#/components/schemas/Alias1"
+                }
+            }
+        "##,
+        r##"
+            {
+                "type": "object",
+                "properties": {
+                    "alias": {
+                        "$ref": "#/components/schemas/Alias1"
+                    },
+                    "name": {
+                        "type": "string"
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:name).value(:string)
+            end
+        "#]],
+    );
+}
@@ -1,10 +1,12 @@
 mod common;
 
 use expect_test::expect;
+use openapi_dry_validation_generator::Config;
 
 #[test]
 fn reference_parameter_from_local() {
-    common::check_with_local_file(
+    common::check_files(
+        common::Entry::Json,
         r##"
             {
                 "openapi": "3.0.0",
@@ -42,9 +44,10 @@ fn reference_parameter_from_local() {
                 }
             }
         "##,
+        Config::default(),
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:string_key).value(:string)
+              optional(:string_key).filled(:string)
             end
         "#]],
     );
@@ -52,7 +55,8 @@ fn reference_parameter_from_local() {
 
 #[test]
 fn reference_parameter_by_json_from_local_file() {
-    common::check_with_local_file(
+    common::check_files(
+        common::Entry::Json,
         r##"
             {
                 "openapi": "3.0.0",
@@ -88,9 +92,10 @@ fn reference_parameter_by_json_from_local_file() {
                 }
             }
         "##,
+        Config::default(),
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:string_key).value(:string)
+              optional(:string_key).filled(:string)
             end
         "#]],
     );
@@ -98,7 +103,8 @@ fn reference_parameter_by_json_from_local_file() {
 
 #[test]
 fn reference_parameter_by_yaml_from_local_file() {
-    common::check_with_local_file(
+    common::check_files(
+        common::Entry::Json,
         r##"
             {
                 "openapi": "3.0.0",
@@ -131,9 +137,10 @@ fn reference_parameter_by_yaml_from_local_file() {
             schema:
                 type: string
         "##,
+        Config::default(),
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:string_key).value(:string)
+              optional(:string_key).filled(:string)
             end
         "#]],
     );
@@ -177,7 +184,66 @@ fn reference_parameter_from_server() {
         &openapi,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:ref_string_key).value(:string)
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn reference_parameter_by_json_pointer() {
+    common::check(
+        r##"
+            {
+                "openapi": "3.0.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/users": {
+                        "get": {
+                            "operationId": "list-users",
+                            "parameters": [
+                                {
+                                    "in": "query",
+                                    "name": "page",
+                                    "schema": {
+                                        "type": "integer"
+                                    }
+                                }
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    },
+                    "/groups": {
+                        "get": {
+                            "operationId": "list-groups",
+                            "parameters": [
+                                {
+                                    "$ref": "#/paths/~1users/get/parameters/0"
+                                }
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            ListUsers = Dry::Schema::Params do
+              optional(:page).filled(:integer)
+            end
+            ListGroups = Dry::Schema::Params do
+              optional(:page).filled(:integer)
             end
         "#]],
     );
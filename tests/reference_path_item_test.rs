@@ -1,10 +1,12 @@
 mod common;
 
 use expect_test::expect;
+use openapi_dry_validation_generator::Config;
 
 #[test]
 fn reference_path_item_from_local_file() {
-    common::check_with_local_file(
+    common::check_files(
+        common::Entry::Json,
         r#"
             {
                 "openapi": "3.0.0",
@@ -40,11 +42,12 @@ fn reference_path_item_from_local_file() {
                 }
             }
         "#,
+        Config::default(),
         expect![[r#"
-        TestExample = Dry::Schema::Params do
-          optional(:ref_string_key).value(:string)
-        end
-    "#]],
+            TestExample = Dry::Schema::Params do
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
     );
 }
 
@@ -86,7 +89,7 @@ fn reference_path_item_by_json_from_server() {
         &openapi,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:ref_string_key).value(:string)
+              optional(:ref_string_key).filled(:string)
             end
         "#]],
     );
@@ -121,8 +124,60 @@ fn reference_path_item_by_yaml_from_server() {
         &openapi,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:ref_string_key).value(:string)
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn reference_path_item_from_local() {
+    common::check_files(
+        common::Entry::Json,
+        r##"
+            {
+                "openapi": "3.0.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/users": {
+                        "$ref": "#/x-shared-paths/users"
+                    },
+                    "/unknown": {
+                        "$ref": "#/x-shared-paths/unknown"
+                    }
+                },
+                "x-shared-paths": {
+                    "users": {
+                        "get": {
+                            "operationId": "listUsers",
+                            "parameters": [
+                                {
+                                    "in": "query",
+                                    "name": "page",
+                                    "schema": {
+                                        "type": "integer"
+                                    }
+                                }
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        Config::default(),
+        expect![[r#"
+            ListUsers = Dry::Schema::Params do
+              optional(:page).filled(:integer)
             end
+            # Failed to resolve reference of path item. path: /unknown, reference: #/x-shared-paths/unknown, error: Not found.(pointer: /x-shared-paths/unknown)
         "#]],
     );
 }
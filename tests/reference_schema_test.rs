@@ -1,10 +1,13 @@
 mod common;
 
 use expect_test::expect;
+use httptest::{matchers::request, responders::status_code, Expectation, Server};
+use openapi_dry_validation_generator::Config;
 
 #[test]
 fn reference_string_schema_from_local() {
-    common::check_with_local_file(
+    common::check_files(
+        common::Entry::Json,
         r##"
             {
                 "openapi": "3.0.0",
@@ -82,21 +85,28 @@ fn reference_string_schema_from_local() {
                 }
             }
         "##,
+        Config::default(),
         expect![[r#"
+            ObjectSchemaSchema = Dry::Schema::JSON do
+            end
+            ObjectSchemaParamsSchema = Dry::Schema::Params do
+            end
             TestExample = Dry::Schema::Params do
-              optional(:string_key).value(:string)
-              optional(:integer_key).value(:integer)
-              optional(:boolean_key).value(:boolean)
-              optional(:array_key).value(:array)
-              optional(:object_key).value(:hash)
+              optional(:string_key).filled(:string)
+              optional(:integer_key).filled(:integer)
+              optional(:boolean_key).filled(:boolean)
+              optional(:array_key).filled(:array)
+              optional(:object_key).filled(:hash, ObjectSchemaParamsSchema)
             end
+            # Rack keeps only the last of repeated keys, so an exploded array is read as `array_key[]=1&array_key[]=2` in array_key
         "#]],
     );
 }
 
 #[test]
 fn reference_schema_by_json_from_local_file() {
-    common::check_with_local_file(
+    common::check_files(
+        common::Entry::Json,
         r##"
             {
                 "openapi": "3.0.0",
@@ -132,9 +142,10 @@ fn reference_schema_by_json_from_local_file() {
                 }
             }
         "##,
+        Config::default(),
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:string_key).value(:string)
+              optional(:string_key).filled(:string)
             end
         "#]],
     );
@@ -142,7 +153,8 @@ fn reference_schema_by_json_from_local_file() {
 
 #[test]
 fn reference_schema_by_yaml_from_local_file() {
-    common::check_with_local_file(
+    common::check_files(
+        common::Entry::Json,
         r##"
             {
                 "openapi": "3.0.0",
@@ -176,9 +188,10 @@ fn reference_schema_by_yaml_from_local_file() {
             ./tests/tmp/foo.yaml
             type: string
         "##,
+        Config::default(),
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:string_key).value(:string)
+              optional(:string_key).filled(:string)
             end
         "#]],
     );
@@ -222,7 +235,566 @@ fn reference_schema_from_server() {
         &openapi,
         expect![[r#"
             TestExample = Dry::Schema::Params do
-              optional(:ref_string_key).value(:string)
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn reference_schema_by_fragment_from_local_file() {
+    common::check_files(
+        common::Entry::Json,
+        r##"
+            {
+                "openapi": "3.0.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/example/test": {
+                        "get": {
+                            "operationId": "test-example",
+                            "parameters": [
+                                {
+                                    "in": "query",
+                                    "name": "error",
+                                    "schema": {
+                                        "$ref": "./tests/tmp/common_schemas.json#/components/schemas/Error"
+                                    }
+                                },
+                                {
+                                    "in": "query",
+                                    "name": "severity",
+                                    "schema": {
+                                        "$ref": "./tests/tmp/common_schemas.yaml#/components/schemas/Severity"
+                                    }
+                                }
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            ---
+            ./tests/tmp/common_schemas.json
+            {
+                "components": {
+                    "schemas": {
+                        "Error": {
+                            "type": "object",
+                            "required": ["code"],
+                            "properties": {
+                                "code": {
+                                    "$ref": "#/components/schemas/Code"
+                                },
+                                "message": {
+                                    "type": "string"
+                                }
+                            }
+                        },
+                        "Code": {
+                            "type": "integer",
+                            "minimum": 100
+                        }
+                    }
+                }
+            }
+            ---
+            ./tests/tmp/common_schemas.yaml
+            components:
+              schemas:
+                Severity:
+                  $ref: "#/components/schemas/Level"
+                Level:
+                  type: string
+                  maxLength: 5
+        "##,
+        Config::default(),
+        expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "error", ["code", "message"])
+                input
+              end
+              optional(:error).filled(:hash) do
+                required(:code).value(:integer, min: 100)
+                optional(:message).value(:string)
+              end
+              optional(:severity).filled(:string, max_size: 5)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn reference_schema_by_fragment_from_server() {
+    let (uri, _server) = common::once_mock_get_200(
+        "/common.json",
+        r##"
+            {
+                "components": {
+                    "schemas": {
+                        "Error": {
+                            "type": "object",
+                            "properties": {
+                                "code": {
+                                    "$ref": "#/components/schemas/Code"
+                                }
+                            }
+                        },
+                        "Code": {
+                            "type": "integer",
+                            "minimum": 100
+                        }
+                    }
+                }
+            }
+        "##,
+    );
+
+    let openapi = common::boilerplate(&format!(
+        r##"
+            "/example/test": {{
+                "get": {{
+                    "operationId": "test-example",
+                    "parameters": [
+                        {{
+                            "in": "query",
+                            "name": "error",
+                            "schema": {{
+                                "$ref": "{uri}#/components/schemas/Error"
+                            }}
+                        }},
+                        {{
+                            "in": "query",
+                            "name": "code",
+                            "schema": {{
+                                "$ref": "{uri}#/components/schemas/Code"
+                            }}
+                        }}
+                    ],
+                    "responses": {{
+                        "200": {{
+                            "description": "OK"
+                        }}
+                    }}
+                }}
+            }}
+        "##
+    ));
+    common::check(
+        &openapi,
+        expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "error", ["code"])
+                input
+              end
+              optional(:error).filled(:hash) do
+                optional(:code).value(:integer, min: 100)
+              end
+              optional(:code).filled(:integer, min: 100)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn reference_schema_relative_to_referencing_file() {
+    common::check_files(
+        common::Entry::RootFile,
+        r##"
+            ./tests/tmp/relative_root.json
+            {
+                "openapi": "3.0.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/example/test": {
+                        "get": {
+                            "operationId": "test-example",
+                            "parameters": [
+                                {
+                                    "in": "query",
+                                    "name": "user",
+                                    "schema": {
+                                        "$ref": "./relative_user.json#/User"
+                                    }
+                                }
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            ---
+            ./tests/tmp/relative_user.json
+            {
+                "User": {
+                    "type": "object",
+                    "properties": {
+                        "address": {
+                            "$ref": "../tmp/relative_address.yaml"
+                        }
+                    }
+                }
+            }
+            ---
+            ./tests/tmp/relative_address.yaml
+            type: object
+            properties:
+              city:
+                type: string
+        "##,
+        Config::default(),
+        expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "user", ["address"])
+                input
+              end
+              optional(:user).filled(:hash) do
+                optional(:address).value(:hash) do
+                  optional(:city).value(:string)
+                end
+              end
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn reference_schema_relative_to_server() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/schemas/user.json"))
+            .times(1)
+            .respond_with(status_code(200).body(
+                r##"
+                    {
+                        "type": "object",
+                        "properties": {
+                            "address": {
+                                "$ref": "address.json#/Address"
+                            }
+                        }
+                    }
+                "##,
+            )),
+    );
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/schemas/address.json"))
+            .times(1)
+            .respond_with(status_code(200).body(
+                r##"
+                    {
+                        "Address": {
+                            "type": "object",
+                            "properties": {
+                                "city": {
+                                    "type": "string"
+                                }
+                            }
+                        }
+                    }
+                "##,
+            )),
+    );
+    let uri = server.url("/schemas/user.json");
+
+    let openapi = common::boilerplate(&format!(
+        r##"
+            "/example/test": {{
+                "get": {{
+                    "operationId": "test-example",
+                    "parameters": [
+                        {{
+                            "in": "query",
+                            "name": "user",
+                            "schema": {{
+                                "$ref": "{uri}"
+                            }}
+                        }}
+                    ],
+                    "responses": {{
+                        "200": {{
+                            "description": "OK"
+                        }}
+                    }}
+                }}
+            }}
+        "##
+    ));
+    common::check(
+        &openapi,
+        expect![[r#"
+            module Coercions
+              # Gathers the keys of an exploded object into the key, e.g. "R=100&G=200" into { "color" => { "R" => "100", "G" => "200" } }.
+              # The key is left as it is if it is given, e.g. as "color[R]=100".
+              def self.gather_keys(input, name, keys)
+                return input if input.key?(name) || input.key?(name.to_sym)
+
+                present = input.keys.select { |key| keys.include?(key.to_s) }
+                return input if present.empty?
+
+                input.except(*present).merge(name => input.slice(*present))
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+              before(:key_coercer) do |result|
+                input = result.to_h
+                input = Coercions.gather_keys(input, "user", ["address"])
+                input
+              end
+              optional(:user).filled(:hash) do
+                optional(:address).value(:hash) do
+                  optional(:city).value(:string)
+                end
+              end
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn reference_schema_by_json_pointer() {
+    common::check(
+        r##"
+            {
+                "openapi": "3.0.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/example/test": {
+                        "get": {
+                            "operationId": "test-example",
+                            "parameters": [
+                                {
+                                    "in": "query",
+                                    "name": "city",
+                                    "schema": {
+                                        "$ref": "#/components/schemas/User/properties/address/properties/city"
+                                    }
+                                },
+                                {
+                                    "in": "query",
+                                    "name": "fields",
+                                    "schema": {
+                                        "$ref": "#/paths/~1users~1%7Bid%7D/get/parameters/0/schema"
+                                    }
+                                },
+                                {
+                                    "in": "query",
+                                    "name": "legacy",
+                                    "schema": {
+                                        "$ref": "#/paths/~1legacy~0v1/get/parameters/0/schema"
+                                    }
+                                },
+                                {
+                                    "in": "query",
+                                    "name": "not_found",
+                                    "schema": {
+                                        "$ref": "#/components/schemas/User/properties/unknown"
+                                    }
+                                }
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    },
+                    "/users/{id}": {
+                        "get": {
+                            "operationId": "get-user",
+                            "parameters": [
+                                {
+                                    "in": "query",
+                                    "name": "fields",
+                                    "schema": {
+                                        "type": "array"
+                                    }
+                                }
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    },
+                    "/legacy~v1": {
+                        "get": {
+                            "operationId": "get-legacy",
+                            "parameters": [
+                                {
+                                    "in": "query",
+                                    "name": "legacy",
+                                    "schema": {
+                                        "type": "boolean"
+                                    }
+                                }
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "schemas": {
+                        "User": {
+                            "type": "object",
+                            "properties": {
+                                "address": {
+                                    "type": "object",
+                                    "properties": {
+                                        "city": {
+                                            "type": "string",
+                                            "maxLength": 10
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            UserSchema = Dry::Schema::JSON do
+              optional(:address).value(:hash) do
+                optional(:city).value(:string, max_size: 10)
+              end
+            end
+            TestExample = Dry::Schema::Params do
+              optional(:city).filled(:string, max_size: 10)
+              optional(:fields).filled(:array)
+              optional(:legacy).filled(:boolean)
+            end
+            GetUser = Dry::Schema::Params do
+              optional(:fields).filled(:array)
+            end
+            GetLegacy = Dry::Schema::Params do
+              optional(:legacy).filled(:boolean)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn reference_schema_not_modeled_by_openapi_30() {
+    common::check_files(
+        common::Entry::Json,
+        r##"
+            {
+                "openapi": "3.0.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/example/test": {
+                        "get": {
+                            "operationId": "test-example",
+                            "parameters": [
+                                {
+                                    "in": "query",
+                                    "name": "code",
+                                    "schema": {
+                                        "$ref": "#/components/schemas/Country/definitions/Code"
+                                    }
+                                }
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "schemas": {
+                        "Country": {
+                            "type": "object",
+                            "definitions": {
+                                "Code": {
+                                    "type": "string",
+                                    "maxLength": 2
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        Config::default(),
+        expect![[r#"
+            CountrySchema = Dry::Schema::JSON do
+            end
+            TestExample = Dry::Schema::Params do
+              optional(:code).filled(:string, max_size: 2)
             end
         "#]],
     );
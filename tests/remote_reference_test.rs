@@ -0,0 +1,450 @@
+mod common;
+
+use std::time::Duration;
+
+use expect_test::{expect, Expect};
+use httptest::{
+    matchers::{all_of, contains, request},
+    responders::{cycle, delay_and_then, status_code},
+    Expectation, Server,
+};
+use openapi_dry_validation_generator::{
+    generate_dry_validation_from_root_json_with_config, CacheConfig, Config, HostHeader,
+    HttpConfig, ResolutionPolicy,
+};
+use tempfile::TempDir;
+
+const STRING_SCHEMA: &str = r#"
+    {
+        "type": "string"
+    }
+"#;
+
+fn openapi_with_schema_reference(server: &Server) -> String {
+    let uri = server.url("/string.json");
+    common::boilerplate(&format!(
+        r#"
+            "/example/test": {{
+                "get": {{
+                    "operationId": "test-example",
+                    "parameters": [
+                        {{
+                            "in": "query",
+                            "name": "ref_string_key",
+                            "schema": {{
+                                "$ref": "{uri}"
+                            }}
+                        }}
+                    ],
+                    "responses": {{
+                        "200": {{
+                            "description": "OK"
+                        }}
+                    }}
+                }}
+            }}
+        "#
+    ))
+}
+
+fn http_config(http: HttpConfig) -> Config {
+    Config {
+        http: HttpConfig {
+            retry_backoff: Duration::from_millis(1),
+            ..http
+        },
+        ..Config::default()
+    }
+}
+
+/// Checks the code and the diagnostics, in which the URL of the server is replaced with `{server}`.
+fn check_remote(server: &Server, config: &Config, expect: Expect) {
+    let generated = generate_dry_validation_from_root_json_with_config(
+        &openapi_with_schema_reference(server),
+        config,
+    );
+    let actual = common::with_diagnostics(generated);
+    let server_url = server.url_str("");
+    expect.assert_eq(&actual.replace(server_url.trim_end_matches('/'), "{server}"));
+}
+
+#[test]
+fn sends_headers_to_host() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("GET", "/string.json"),
+            request::headers(contains(("authorization", "Bearer secret"))),
+        ])
+        .times(1)
+        .respond_with(status_code(200).body(STRING_SCHEMA)),
+    );
+
+    let config = http_config(HttpConfig {
+        headers: vec![
+            format!("{}=Authorization: Bearer secret", server.addr())
+                .parse()
+                .unwrap(),
+            HostHeader {
+                host: "example.com".to_string(),
+                name: "Authorization".to_string(),
+                value: "Bearer other".to_string(),
+            },
+        ],
+        ..HttpConfig::default()
+    });
+    check_remote(
+        &server,
+        &config,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+}
+
+/// An IPv6 host is bracketed in the URL, e.g. `[::1]`, but it may be given without brackets.
+#[test]
+fn sends_headers_to_host_without_port() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("GET", "/string.json"),
+            request::headers(contains(("authorization", "Bearer secret"))),
+        ])
+        .times(1)
+        .respond_with(status_code(200).body(STRING_SCHEMA)),
+    );
+
+    let config = http_config(HttpConfig {
+        headers: vec![
+            format!("{}=Authorization: Bearer secret", server.addr().ip())
+                .parse()
+                .unwrap(),
+        ],
+        ..HttpConfig::default()
+    });
+    check_remote(
+        &server,
+        &config,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn client_error_is_diagnostic() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/string.json"))
+            .times(1)
+            .respond_with(status_code(404)),
+    );
+
+    check_remote(
+        &server,
+        &http_config(HttpConfig::default()),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            # Failed to resolve reference. reference: {server}/string.json, error: Failed to fetch.(url: {server}/string.json, status: 404 Not Found)
+        "#]],
+    );
+}
+
+#[test]
+fn retries_server_error() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/string.json"))
+            .times(2)
+            .respond_with(cycle![
+                status_code(503),
+                status_code(200).body(STRING_SCHEMA),
+            ]),
+    );
+
+    check_remote(
+        &server,
+        &http_config(HttpConfig {
+            retries: 1,
+            ..HttpConfig::default()
+        }),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn gives_up_after_retries() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/string.json"))
+            .times(3)
+            .respond_with(status_code(500)),
+    );
+
+    check_remote(
+        &server,
+        &http_config(HttpConfig {
+            retries: 2,
+            ..HttpConfig::default()
+        }),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            # Failed to resolve reference. reference: {server}/string.json, error: Failed to fetch.(url: {server}/string.json, status: 500 Internal Server Error)
+        "#]],
+    );
+}
+
+#[test]
+fn times_out() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/string.json"))
+            .times(1)
+            .respond_with(delay_and_then(
+                Duration::from_millis(500),
+                status_code(200).body(STRING_SCHEMA),
+            )),
+    );
+
+    check_remote(
+        &server,
+        &http_config(HttpConfig {
+            timeout: Duration::from_millis(50),
+            retries: 0,
+            ..HttpConfig::default()
+        }),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            # Failed to resolve reference. reference: {server}/string.json, error: error sending request for url ({server}/string.json): operation timed out
+        "#]],
+    );
+}
+
+fn cache_config(dir: &TempDir, offline: bool, frozen: bool) -> Config {
+    Config {
+        cache: Some(CacheConfig {
+            dir: dir.path().join("cache"),
+            lockfile: dir.path().join("openapi.lock.json"),
+            offline,
+            frozen,
+        }),
+        ..http_config(HttpConfig::default())
+    }
+}
+
+#[test]
+fn caches_and_locks_documents() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/string.json"))
+            .times(1)
+            .respond_with(status_code(200).body(STRING_SCHEMA)),
+    );
+    let dir = tempfile::tempdir().unwrap();
+
+    check_remote(
+        &server,
+        &cache_config(&dir, false, false),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+
+    let lockfile = std::fs::read_to_string(dir.path().join("openapi.lock.json")).unwrap();
+    let server_url = server.url_str("");
+    expect![[r#"
+        {
+          "{server}/string.json": "sha256:7cdb9e91d116912650e455438309bd473710d42def45e244e66429dc535f0bd8"
+        }
+    "#]].assert_eq(&lockfile.replace(server_url.trim_end_matches('/'), "{server}"));
+
+    // Not fetched again.
+    check_remote(
+        &server,
+        &cache_config(&dir, true, true),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn offline_without_cache() {
+    let server = Server::run();
+    let dir = tempfile::tempdir().unwrap();
+
+    check_remote(
+        &server,
+        &cache_config(&dir, true, false),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            # Failed to resolve reference. reference: {server}/string.json, error: Not cached in offline mode.(url: {server}/string.json)
+        "#]],
+    );
+}
+
+#[test]
+fn frozen_fails_on_changed_document() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/string.json"))
+            .times(2)
+            .respond_with(cycle![
+                status_code(200).body(STRING_SCHEMA),
+                status_code(200).body(r#"{ "type": "integer" }"#),
+            ]),
+    );
+    let dir = tempfile::tempdir().unwrap();
+
+    check_remote(
+        &server,
+        &cache_config(&dir, false, false),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+    check_remote(
+        &server,
+        &cache_config(&dir, false, true),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            # Failed to resolve reference. reference: {server}/string.json, error: Hash of the document has changed.(url: {server}/string.json, locked: sha256:7cdb9e91d116912650e455438309bd473710d42def45e244e66429dc535f0bd8, actual: sha256:be8e752668e6e615cb2949933cb6edb37cfcc3ed90d749b6c98a71bb65ebd497)
+        "#]],
+    );
+    // The locked document is still cached.
+    check_remote(
+        &server,
+        &cache_config(&dir, true, true),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn offline_leaves_lockfile() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/string.json"))
+            .times(1)
+            .respond_with(status_code(200).body(STRING_SCHEMA)),
+    );
+    let dir = tempfile::tempdir().unwrap();
+    let lockfile = dir.path().join("openapi.lock.json");
+
+    check_remote(
+        &server,
+        &cache_config(&dir, false, false),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+    std::fs::remove_file(&lockfile).unwrap();
+    check_remote(
+        &server,
+        &cache_config(&dir, true, false),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:ref_string_key).filled(:string)
+            end
+        "#]],
+    );
+    assert!(!lockfile.exists());
+}
+
+#[test]
+fn broken_lockfile_is_diagnostic() {
+    let server = Server::run();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("openapi.lock.json"), "broken").unwrap();
+
+    let generated = generate_dry_validation_from_root_json_with_config(
+        &openapi_with_schema_reference(&server),
+        &cache_config(&dir, false, false),
+    );
+    let actual = common::with_diagnostics(generated);
+    expect![[r#"
+        # Failed to set up the loader. error: The lockfile is broken.(path: {dir}/openapi.lock.json, error: expected value at line 1 column 1)
+    "#]]
+    .assert_eq(&actual.replace(&dir.path().display().to_string(), "{dir}"));
+}
+
+#[test]
+fn large_document_is_not_cached() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/string.json"))
+            .respond_with(status_code(200).body(STRING_SCHEMA)),
+    );
+    let dir = tempfile::tempdir().unwrap();
+
+    check_remote(
+        &server,
+        &Config {
+            resolution: ResolutionPolicy {
+                max_document_size: Some(10),
+                ..ResolutionPolicy::default()
+            },
+            ..cache_config(&dir, false, false)
+        },
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            # Failed to resolve reference. reference: {server}/string.json, error: The document is too large.(url: {server}/string.json, size: 38, max: 10)
+        "#]],
+    );
+    assert!(!dir.path().join("openapi.lock.json").exists());
+    check_remote(
+        &server,
+        &cache_config(&dir, true, false),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            # Failed to resolve reference. reference: {server}/string.json, error: Not cached in offline mode.(url: {server}/string.json)
+        "#]],
+    );
+}
+
+#[test]
+fn remote_document_can_not_refer_to_file() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/string.json"))
+            .times(1)
+            .respond_with(status_code(200).body(r#"{ "$ref": "file:///etc/secret.json" }"#)),
+    );
+
+    check_remote(
+        &server,
+        &http_config(HttpConfig::default()),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            # Failed to resolve reference. reference: {server}/string.json, error: A file can only be referred to from a local document.(url: file:///etc/secret.json)
+        "#]],
+    );
+}
@@ -0,0 +1,408 @@
+mod common;
+
+use expect_test::{expect, Expect};
+
+use openapi_dry_validation_generator::{Config, ReadOnlyPolicy};
+
+fn request_body(content: &str) -> String {
+    common::boilerplate(&format!(
+        r#"
+                "/test/example": {{
+                    "post": {{
+                        "operationId": "testExample",
+                        "requestBody": {{
+                            "content": {content}
+                        }},
+                        "responses": {{
+                            "200": {{
+                                "description": "OK"
+                            }}
+                        }}
+                    }}
+                }}
+            "#
+    ))
+}
+
+fn check_request_body(actual: &str, expect: Expect) {
+    common::check(&request_body(actual), expect);
+}
+
+fn check_request_body_with_config(actual: &str, config: &Config, expect: Expect) {
+    common::check_with_config(&request_body(actual), config, expect);
+}
+
+#[test]
+fn request_body_properties() {
+    check_request_body(
+        r#"
+                {
+                    "application/json": {
+                        "schema": {
+                            "type": "object",
+                            "required": ["name"],
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "maxLength": 10
+                                },
+                                "age": {
+                                    "type": "integer"
+                                },
+                                "address": {
+                                    "type": "object",
+                                    "properties": {
+                                        "city": {
+                                            "type": "string"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              required(:name).value(:string, max_size: 10)
+              optional(:age).value(:integer)
+              optional(:address).value(:hash) do
+                optional(:city).value(:string)
+              end
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn request_body_media_types() {
+    check_request_body(
+        r#"
+                {
+                    "text/plain": {
+                        "schema": {
+                            "type": "string"
+                        }
+                    },
+                    "application/merge-patch+json; charset=utf-8": {
+                        "schema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string"
+                                }
+                            }
+                        }
+                    }
+                }
+            "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:name).value(:string)
+            end
+        "#]],
+    );
+
+    check_request_body(
+        r#"
+                {
+                    "text/plain": {
+                        "schema": {
+                            "type": "string"
+                        }
+                    }
+                }
+            "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn request_body_form() {
+    check_request_body(
+        r#"
+                {
+                    "application/x-www-form-urlencoded": {
+                        "schema": {
+                            "type": "object",
+                            "required": ["name"],
+                            "properties": {
+                                "name": {
+                                    "type": "string"
+                                },
+                                "age": {
+                                    "type": "integer"
+                                }
+                            }
+                        }
+                    }
+                }
+            "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::Params do
+              required(:name).value(:string)
+              optional(:age).value(:integer)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn request_body_unsupported_media_type() {
+    common::check_files(
+        common::Entry::Json,
+        &request_body(
+            r#"
+                {
+                    "text/plain": {
+                        "schema": {
+                            "type": "string"
+                        }
+                    }
+                }
+            "#,
+        ),
+        Config::default(),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            # Only JSON and forms are supported in requestBody. media types: text/plain
+        "#]],
+    );
+}
+
+#[test]
+fn request_body_must_be_object() {
+    check_request_body(
+        r#"
+                {
+                    "application/json": {
+                        "schema": {
+                            "type": "array",
+                            "items": {
+                                "type": "string"
+                            }
+                        }
+                    }
+                }
+            "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+        "#]],
+    );
+}
+
+const READ_ONLY_AND_WRITE_ONLY: &str = r#"
+                {
+                    "application/json": {
+                        "schema": {
+                            "type": "object",
+                            "properties": {
+                                "id": {
+                                    "type": "integer",
+                                    "readOnly": true,
+                                    "default": 1
+                                },
+                                "password": {
+                                    "type": "string",
+                                    "writeOnly": true
+                                },
+                                "profile": {
+                                    "type": "object",
+                                    "properties": {
+                                        "created_at": {
+                                            "type": "string",
+                                            "readOnly": true
+                                        },
+                                        "nickname": {
+                                            "type": "string",
+                                            "default": "anonymous"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            "#;
+
+#[test]
+fn request_body_ignores_read_only() {
+    check_request_body(
+        READ_ONLY_AND_WRITE_ONLY,
+        expect![[r#"
+            module Defaults
+              # Defaults of the keys of a hash, which are filled only if the hash is given or has a default of its own.
+              Nested = Struct.new(:defaults, :default)
+
+              # Fills the keys missing from the input with their defaults, e.g. `Defaults.apply(params, ShowUserDefaults)`.
+              def self.apply(input, defaults)
+                return input unless input.is_a?(Hash)
+
+                defaults.each_with_object(input.dup) do |(key, default), output|
+                  if !default.is_a?(Nested)
+                    output[key] = default unless output.key?(key)
+                  elsif output.key?(key) || !default.default.nil?
+                    output[key] = apply(output.fetch(key, default.default), default.defaults)
+                  end
+                end
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:password).value(:string)
+              optional(:profile).value(:hash) do
+                optional(:nickname).value(:string)
+              end
+            end
+            TestExampleRequestBodyDefaults = {
+              "profile" => Defaults::Nested.new({
+                "nickname" => "anonymous"
+              }.freeze)
+            }.freeze
+        "#]],
+    );
+}
+
+#[test]
+fn request_body_rejects_read_only() {
+    check_request_body_with_config(
+        READ_ONLY_AND_WRITE_ONLY,
+        &Config {
+            read_only: ReadOnlyPolicy::Reject,
+            ..Config::default()
+        },
+        expect![[r#"
+            module Rules
+              # Yields each value at the path with its full path, where :* stands for every element of an array.
+              def self.each_value(value, path, value_path = [], &block)
+                return yield(value, value_path) if path.empty?
+
+                key, *rest = path
+                if key == :*
+                  return unless value.is_a?(Array)
+
+                  value.each_with_index { |item, index| each_value(item, rest, [*value_path, index], &block) }
+                elsif value.is_a?(Hash) && value.key?(key)
+                  each_value(value[key], rest, [*value_path, key], &block)
+                end
+              end
+
+              def self.forbidden(rule, path, message)
+                each_value(rule.values.data, path) { |_value, value_path| rule.key(value_path).failure(message) }
+              end
+            end
+
+            module Defaults
+              # Defaults of the keys of a hash, which are filled only if the hash is given or has a default of its own.
+              Nested = Struct.new(:defaults, :default)
+
+              # Fills the keys missing from the input with their defaults, e.g. `Defaults.apply(params, ShowUserDefaults)`.
+              def self.apply(input, defaults)
+                return input unless input.is_a?(Hash)
+
+                defaults.each_with_object(input.dup) do |(key, default), output|
+                  if !default.is_a?(Nested)
+                    output[key] = default unless output.key?(key)
+                  elsif output.key?(key) || !default.default.nil?
+                    output[key] = apply(output.fetch(key, default.default), default.defaults)
+                  end
+                end
+              end
+            end
+
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Class.new(Dry::Validation::Contract) do
+              json do
+                optional(:id).value(:integer)
+                optional(:password).value(:string)
+                optional(:profile).value(:hash) do
+                  optional(:created_at).value(:string)
+                  optional(:nickname).value(:string)
+                end
+              end
+
+              rule do
+                Rules.forbidden(self, [:id], "must not be sent, since it is read-only")
+                Rules.forbidden(self, [:profile, :created_at], "must not be sent, since it is read-only")
+              end
+            end.new
+            TestExampleRequestBodyDefaults = {
+              "profile" => Defaults::Nested.new({
+                "nickname" => "anonymous"
+              }.freeze)
+            }.freeze
+        "#]],
+    );
+}
+
+#[test]
+fn request_body_reference() {
+    common::check(
+        r##"
+            {
+                "openapi": "3.0.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/test/example": {
+                        "post": {
+                            "operationId": "testExample",
+                            "requestBody": {
+                                "$ref": "#/components/requestBodies/UserBody"
+                            },
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "requestBodies": {
+                        "UserBody": {
+                            "$ref": "#/components/requestBodies/NamedBody"
+                        },
+                        "NamedBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "name": {
+                                                "type": "string"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleRequestBody = Dry::Schema::JSON do
+              optional(:name).value(:string)
+            end
+        "#]],
+    );
+}
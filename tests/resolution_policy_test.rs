@@ -0,0 +1,200 @@
+mod common;
+
+use expect_test::expect;
+use openapi_dry_validation_generator::{Config, ResolutionPolicy};
+
+fn openapi_with_schema_references(references: &[&str]) -> String {
+    let parameters = references
+        .iter()
+        .enumerate()
+        .map(|(i, reference)| {
+            format!(
+                r#"
+                    {{
+                        "in": "query",
+                        "name": "key{i}",
+                        "schema": {{
+                            "$ref": "{reference}"
+                        }}
+                    }}
+                "#
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    common::boilerplate(&format!(
+        r#"
+            "/example/test": {{
+                "get": {{
+                    "operationId": "test-example",
+                    "parameters": [{parameters}],
+                    "responses": {{
+                        "200": {{
+                            "description": "OK"
+                        }}
+                    }}
+                }}
+            }}
+        "#
+    ))
+}
+
+fn policy(resolution: ResolutionPolicy) -> Config {
+    Config {
+        resolution,
+        ..Config::default()
+    }
+}
+
+#[test]
+fn local_reference_outside_of_root_dir() {
+    let openapi = openapi_with_schema_references(&[
+        "./tests/schemas/inside.json",
+        "./tests/schemas/../../outside.json",
+    ]);
+    common::check_files(
+        common::Entry::Json,
+        &format!(
+            r#"{openapi}
+            ---
+            ./tests/schemas/inside.json
+            {{ "type": "string" }}
+            ---
+            ./outside.json
+            {{ "type": "integer" }}
+            "#
+        ),
+        policy(ResolutionPolicy {
+            root_dir: Some(std::env::current_dir().unwrap().join("tests")),
+            ..ResolutionPolicy::default()
+        }),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:key0).filled(:string)
+            end
+            # Failed to resolve reference. reference: ./tests/schemas/../../outside.json, error: The file is outside of the root directory.(url: file://{current_dir}/outside.json, root: {current_dir}/tests)
+        "#]],
+    );
+}
+
+#[test]
+fn remote_reference_not_allowed() {
+    let openapi = openapi_with_schema_references(&[
+        "https://schemas.example.com/string.json",
+        "https://evil.example.com/string.json",
+        "http://schemas.example.com/string.json",
+    ]);
+    common::check_files(
+        common::Entry::Json,
+        &format!(
+            r#"{openapi}
+            ---
+            https://schemas.example.com/string.json
+            {{ "type": "string" }}
+            "#
+        ),
+        policy(ResolutionPolicy {
+            allowed_schemes: vec!["https".to_string()],
+            allowed_hosts: Some(vec!["schemas.example.com".to_string()]),
+            ..ResolutionPolicy::default()
+        }),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:key0).filled(:string)
+            end
+            # Failed to resolve reference. reference: https://evil.example.com/string.json, error: The host is not allowed.(url: https://evil.example.com/string.json)
+            # Failed to resolve reference. reference: http://schemas.example.com/string.json, error: The scheme is not allowed.(url: http://schemas.example.com/string.json)
+        "#]],
+    );
+}
+
+#[test]
+fn documents_over_limits() {
+    let openapi = openapi_with_schema_references(&[
+        "./tests/schemas/small.json",
+        "./tests/schemas/large.json",
+        "./tests/schemas/other.json",
+        "./tests/schemas/another.json",
+    ]);
+    common::check_files(
+        common::Entry::Json,
+        &format!(
+            r#"{openapi}
+            ---
+            ./tests/schemas/small.json
+            {{ "type": "string" }}
+            ---
+            ./tests/schemas/large.json
+            {{ "type": "string", "maxLength": 10, "description": "Too large to load" }}
+            ---
+            ./tests/schemas/other.json
+            {{ "type": "integer" }}
+            ---
+            ./tests/schemas/another.json
+            {{ "type": "boolean" }}
+            "#
+        ),
+        policy(ResolutionPolicy {
+            max_document_size: Some(60),
+            max_documents: Some(2),
+            ..ResolutionPolicy::default()
+        }),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:key0).filled(:string)
+              optional(:key2).filled(:integer)
+            end
+            # Failed to resolve reference. reference: ./tests/schemas/large.json, error: The document is too large.(url: file://{current_dir}/tests/schemas/large.json, size: 98, max: 60)
+            # Failed to resolve reference. reference: ./tests/schemas/another.json, error: Too many documents.(url: file://{current_dir}/tests/schemas/another.json, max: 2)
+        "#]],
+    );
+}
+
+#[test]
+fn parameter_reference_outside_of_root_dir() {
+    let openapi = common::boilerplate(
+        r#"
+            "/example/test": {
+                "get": {
+                    "operationId": "test-example",
+                    "parameters": [
+                        {
+                            "$ref": "./tests/params/inside.json"
+                        },
+                        {
+                            "$ref": "./tests/params/../../outside.json"
+                        }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "OK"
+                        }
+                    }
+                }
+            }
+        "#,
+    );
+    common::check_files(
+        common::Entry::Json,
+        &format!(
+            r#"{openapi}
+            ---
+            ./tests/params/inside.json
+            {{ "in": "query", "name": "inside", "schema": {{ "type": "string" }} }}
+            ---
+            ./outside.json
+            {{ "in": "query", "name": "outside", "schema": {{ "type": "integer" }} }}
+            "#
+        ),
+        policy(ResolutionPolicy {
+            root_dir: Some(std::env::current_dir().unwrap().join("tests")),
+            ..ResolutionPolicy::default()
+        }),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+              optional(:inside).filled(:string)
+            end
+            # Failed to resolve reference. reference: ./tests/params/../../outside.json, error: The file is outside of the root directory.(url: file://{current_dir}/outside.json, root: {current_dir}/tests)
+        "#]],
+    );
+}
@@ -0,0 +1,247 @@
+mod common;
+
+use expect_test::{expect, Expect};
+use openapi_dry_validation_generator::Config;
+
+fn check_responses(actual: &str, expect: Expect) {
+    let actual = common::boilerplate(&format!(
+        r#"
+                "/test/example": {{
+                    "get": {{
+                        "operationId": "testExample",
+                        "responses": {actual}
+                    }}
+                }}
+            "#
+    ));
+    common::check(&actual, expect);
+}
+
+#[test]
+fn responses() {
+    check_responses(
+        r#"
+                {
+                    "200": {
+                        "description": "OK",
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "type": "object",
+                                    "required": ["id"],
+                                    "properties": {
+                                        "id": {
+                                            "type": "integer"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "204": {
+                        "description": "No Content"
+                    },
+                    "4XX": {
+                        "description": "Client Error",
+                        "content": {
+                            "application/problem+json": {
+                                "schema": {
+                                    "type": "object",
+                                    "properties": {
+                                        "title": {
+                                            "type": "string"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "default": {
+                        "description": "Unexpected Error",
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "type": "object",
+                                    "properties": {
+                                        "message": {
+                                            "type": "string"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleResponse200 = Dry::Schema::JSON do
+              required(:id).value(:integer)
+            end
+            TestExampleResponse4Xx = Dry::Schema::JSON do
+              optional(:title).value(:string)
+            end
+            TestExampleResponseDefault = Dry::Schema::JSON do
+              optional(:message).value(:string)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn response_drops_write_only() {
+    check_responses(
+        r#"
+                {
+                    "200": {
+                        "description": "OK",
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "type": "object",
+                                    "properties": {
+                                        "id": {
+                                            "type": "integer",
+                                            "readOnly": true
+                                        },
+                                        "password": {
+                                            "type": "string",
+                                            "writeOnly": true
+                                        },
+                                        "profile": {
+                                            "type": "object",
+                                            "properties": {
+                                                "secret": {
+                                                    "type": "string",
+                                                    "writeOnly": true
+                                                },
+                                                "nickname": {
+                                                    "type": "string"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            "#,
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleResponse200 = Dry::Schema::JSON do
+              optional(:id).value(:integer)
+              optional(:profile).value(:hash) do
+                optional(:nickname).value(:string)
+              end
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn response_reference() {
+    common::check_files(
+        common::Entry::Json,
+        r##"
+            {
+                "openapi": "3.0.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/test/example": {
+                        "get": {
+                            "operationId": "testExample",
+                            "responses": {
+                                "200": {
+                                    "$ref": "#/components/responses/User"
+                                },
+                                "default": {
+                                    "$ref": "./tests/tmp/common_responses.json#/Error"
+                                }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "responses": {
+                        "User": {
+                            "description": "OK",
+                            "headers": {
+                                "X-Rate-Limit": {
+                                    "$ref": "#/components/headers/RateLimit"
+                                },
+                                "X-Request-Id": {
+                                    "schema": {
+                                        "type": "string"
+                                    }
+                                }
+                            },
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "id": {
+                                                "type": "integer"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "headers": {
+                        "RateLimit": {
+                            "required": true,
+                            "schema": {
+                                "type": "integer"
+                            }
+                        }
+                    }
+                }
+            }
+            ---
+            ./tests/tmp/common_responses.json
+            {
+                "Error": {
+                    "description": "Error",
+                    "content": {
+                        "application/json": {
+                            "schema": {
+                                "$ref": "#/ErrorBody"
+                            }
+                        }
+                    }
+                },
+                "ErrorBody": {
+                    "type": "object",
+                    "properties": {
+                        "message": {
+                            "type": "string"
+                        }
+                    }
+                }
+            }
+        "##,
+        Config::default(),
+        expect![[r#"
+            TestExample = Dry::Schema::Params do
+            end
+            TestExampleResponse200Headers = Dry::Schema::Params do
+              required(:"X-Rate-Limit").value(:integer)
+              optional(:"X-Request-Id").value(:string)
+            end
+            TestExampleResponse200 = Dry::Schema::JSON do
+              optional(:id).value(:integer)
+            end
+            TestExampleResponseDefault = Dry::Schema::JSON do
+              optional(:message).value(:string)
+            end
+        "#]],
+    );
+}
@@ -0,0 +1,329 @@
+mod common;
+
+use expect_test::{expect, Expect};
+use openapi_dry_validation_generator::{
+    generate_dry_validation_from_root_json_with_config, Config,
+};
+
+fn check(actual: &str, expect: Expect) {
+    let generated = generate_dry_validation_from_root_json_with_config(actual, &Config::default());
+    expect.assert_eq(&common::with_diagnostics(generated));
+}
+
+#[test]
+fn body_and_definitions() {
+    check(
+        r##"
+            {
+                "swagger": "2.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "host": "example.com",
+                "basePath": "/v1",
+                "paths": {
+                    "/users": {
+                        "post": {
+                            "operationId": "createUser",
+                            "parameters": [
+                                {
+                                    "in": "query",
+                                    "name": "ids",
+                                    "type": "array",
+                                    "items": {
+                                        "type": "integer"
+                                    },
+                                    "collectionFormat": "csv"
+                                },
+                                {
+                                    "in": "query",
+                                    "name": "tags",
+                                    "type": "array",
+                                    "items": {
+                                        "type": "string"
+                                    },
+                                    "collectionFormat": "tsv"
+                                },
+                                {
+                                    "in": "body",
+                                    "name": "body",
+                                    "required": true,
+                                    "schema": {
+                                        "$ref": "#/definitions/User"
+                                    }
+                                }
+                            ],
+                            "responses": {
+                                "201": {
+                                    "description": "Created",
+                                    "schema": {
+                                        "$ref": "#/definitions/User"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                "definitions": {
+                    "User": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {
+                            "name": {
+                                "type": "string",
+                                "x-nullable": true
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            module Coercions
+              # Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+              def self.split(input, name, delimiter, prefix: "")
+                value = input[name]
+                return input unless value.is_a?(String) && value.start_with?(prefix)
+
+                input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+              end
+            end
+
+            UserSchema = Dry::Schema::JSON do
+              required(:name).maybe(:string)
+            end
+            CreateUser = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                input = Coercions.split(input, :ids, ",")
+                input
+              end
+              optional(:ids).filled(:array).each(:int?)
+              optional(:tags).filled(:array).each(:str?)
+            end
+            CreateUserRequestBody = Dry::Schema::JSON do
+              required(:name).maybe(:string)
+            end
+            CreateUserResponse201 = Dry::Schema::JSON do
+              required(:name).maybe(:string)
+            end
+            # `collectionFormat` is not supported. format: tsv, parameter: tags, in post /users
+            # Rack keeps only the last of repeated keys, so an exploded array is read as `tags[]=1&tags[]=2` in tags
+        "#]],
+    );
+}
+
+#[test]
+fn form_data_and_global_parameters() {
+    check(
+        r##"
+            {
+                "swagger": "2.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "consumes": ["application/x-www-form-urlencoded"],
+                "paths": {
+                    "/users": {
+                        "get": {
+                            "operationId": "listUsers",
+                            "parameters": [
+                                {
+                                    "$ref": "#/parameters/Limit"
+                                }
+                            ],
+                            "responses": {
+                                "200": {
+                                    "$ref": "#/responses/Users"
+                                }
+                            }
+                        },
+                        "post": {
+                            "operationId": "createUser",
+                            "parameters": [
+                                {
+                                    "in": "formData",
+                                    "name": "name",
+                                    "type": "string",
+                                    "required": true
+                                },
+                                {
+                                    "in": "formData",
+                                    "name": "age",
+                                    "type": "integer",
+                                    "minimum": 0
+                                }
+                            ],
+                            "responses": {
+                                "201": {
+                                    "description": "Created"
+                                }
+                            }
+                        },
+                        "put": {
+                            "operationId": "replaceUsers",
+                            "consumes": ["application/json"],
+                            "parameters": [
+                                {
+                                    "$ref": "#/parameters/Users"
+                                }
+                            ],
+                            "responses": {
+                                "204": {
+                                    "description": "No Content"
+                                }
+                            }
+                        }
+                    }
+                },
+                "parameters": {
+                    "Limit": {
+                        "in": "query",
+                        "name": "limit",
+                        "type": "integer",
+                        "maximum": 100
+                    },
+                    "Users": {
+                        "in": "body",
+                        "name": "body",
+                        "schema": {
+                            "type": "object",
+                            "properties": {
+                                "names": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "string"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                "responses": {
+                    "Users": {
+                        "description": "OK",
+                        "schema": {
+                            "type": "object",
+                            "properties": {
+                                "total": {
+                                    "type": "integer"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            ListUsers = Dry::Schema::Params do
+              optional(:limit).filled(:integer, max: 100)
+            end
+            ListUsersResponse200 = Dry::Schema::JSON do
+              optional(:total).value(:integer)
+            end
+            CreateUser = Dry::Schema::Params do
+            end
+            CreateUserRequestBody = Dry::Schema::Params do
+              required(:name).value(:string)
+              optional(:age).value(:integer, min: 0)
+            end
+            ReplaceUsers = Dry::Schema::Params do
+            end
+            ReplaceUsersRequestBody = Dry::Schema::JSON do
+              optional(:names).value(:array).each(:str?)
+            end
+        "#]],
+    );
+}
+
+#[test]
+fn array_parameters_default_to_csv() {
+    check(
+        r##"
+            {
+                "swagger": "2.0",
+                "info": {
+                    "title": "Testing API overview",
+                    "version": "1.0.0"
+                },
+                "paths": {
+                    "/users/{ids}": {
+                        "get": {
+                            "operationId": "getUsers",
+                            "parameters": [
+                                {
+                                    "in": "path",
+                                    "name": "ids",
+                                    "required": true,
+                                    "type": "array",
+                                    "items": {
+                                        "type": "integer"
+                                    }
+                                },
+                                {
+                                    "in": "query",
+                                    "name": "fields",
+                                    "type": "array",
+                                    "items": {
+                                        "type": "string"
+                                    }
+                                },
+                                {
+                                    "in": "header",
+                                    "name": "X-Tags",
+                                    "type": "array",
+                                    "items": {
+                                        "type": "string"
+                                    }
+                                }
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "OK"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "##,
+        expect![[r#"
+            module Coercions
+              # Splits the string of the key into an array, e.g. "1,2,3" into ["1", "2", "3"].
+              def self.split(input, name, delimiter, prefix: "")
+                value = input[name]
+                return input unless value.is_a?(String) && value.start_with?(prefix)
+
+                input.merge(name => value.delete_prefix(prefix).split(delimiter, -1))
+              end
+            end
+
+            GetUsers = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                input = Coercions.split(input, :fields, ",")
+                input
+              end
+              optional(:fields).filled(:array).each(:str?)
+            end
+            GetUsersPathParameters = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                input = Coercions.split(input, :ids, ",")
+                input
+              end
+              required(:ids).value(:array).each(:int?)
+            end
+            GetUsersHeaders = Dry::Schema::Params do
+              before(:value_coercer) do |result|
+                input = result.to_h
+                input = Coercions.split(input, :"X-Tags", ",")
+                input
+              end
+              optional(:"X-Tags").value(:array).each(:str?)
+            end
+        "#]],
+    );
+}